@@ -1,5 +1,9 @@
 //! request/result types for using the api directly.
 //!
+//! Each endpoint is described by a type implementing [`Endpoint`], which can build the http
+//! request for it and parse the response without doing any io itself - so with just this module
+//! you can drive the api from whatever http library / runtime you like.
+//!
 // TODO rewrite this line
 //! If you just want to call the api, you probably want the [`client`][crate::client] module instead.
 
@@ -8,6 +12,23 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use time::OffsetDateTime;
 
+mod endpoint;
+pub(crate) mod multipart;
+mod pagination;
+
+#[cfg(feature = "client")]
+pub(crate) use endpoint::{check_mime_type, multipart_parts};
+pub use endpoint::{
+    Attachment, AvatarEndpoint, BlogInfoEndpoint, CreatePostEndpoint, DeletePostEndpoint,
+    EditTagsEndpoint, Endpoint, EndpointError, LikeEndpoint, PostEndpoint, PreparedBody,
+    PreparedPart, PreparedRequest, RawEndpoint, ReblogEndpoint, RequestBody, UserInfoEndpoint,
+    UserLimitsEndpoint,
+};
+pub use pagination::{
+    BlogLikesEndpoint, BlogPostsEndpoint, Cursor, DashboardEndpoint, DraftsEndpoint,
+    FollowersEndpoint, NotesEndpoint, Page, Paginated,
+};
+
 // https://www.tumblr.com/docs/en/api/v2#postspost-id---fetching-a-post-neue-post-format
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 // #[serde(deny_unknown_fields)]
@@ -172,6 +193,7 @@ mod post_submission_info_serde {
     #[derive(Serialize)]
     struct ShimEmpty {}
 
+    #[allow(clippy::ref_option)] // signature is dictated by `#[serde(with)]`
     pub(super) fn serialize<S>(
         opt: &Option<SubmissionInfo>,
         serializer: S,
@@ -282,7 +304,77 @@ pub struct InteractabilityInfo {
 pub struct ResponseErrorEntry {
     // TODO should title/code be `Option`al? are they ever not included?
     pub title: String,
+    /// the error's subcode. see [`error_code`][ResponseErrorEntry::error_code] for a typed version.
     pub code: i32,
+    /// a longer, human-readable description of the error, if the api included one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ResponseErrorEntry {
+    #[must_use]
+    pub const fn error_code(&self) -> ApiErrorCode {
+        ApiErrorCode::from_code(self.code)
+    }
+}
+
+/// the error subcodes the api reports in [`ResponseErrorEntry::code`].
+///
+/// many errors (e.g. a post not being found) don't get a subcode of their own, and are
+/// reported with [`ApiErrorCode::Unspecified`] - for those, the http status in
+/// [`ResponseMeta::status`] is what tells them apart.
+// https://www.tumblr.com/docs/en/api/v2#errors-and-error-subcodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiErrorCode {
+    /// `0`, i.e. no particular subcode
+    Unspecified,
+    /// `5xxx`, some problem with authentication or authorization
+    Auth(i32),
+    /// `1016`, the post couldn't be created because the user has hit their daily post limit
+    PostLimitExceeded,
+    /// `4012`, the blog doesn't exist
+    BlogNotFound,
+    /// `8001`, the post content wasn't valid NPF
+    InvalidNpf,
+    /// any other subcode
+    Unknown(i32),
+}
+
+impl ApiErrorCode {
+    #[must_use]
+    pub const fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Unspecified,
+            1016 => Self::PostLimitExceeded,
+            4012 => Self::BlogNotFound,
+            5000..=5999 => Self::Auth(code),
+            8001 => Self::InvalidNpf,
+            _ => Self::Unknown(code),
+        }
+    }
+
+    #[must_use]
+    pub const fn code(self) -> i32 {
+        match self {
+            Self::Unspecified => 0,
+            Self::PostLimitExceeded => 1016,
+            Self::BlogNotFound => 4012,
+            Self::InvalidNpf => 8001,
+            Self::Auth(code) | Self::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<i32> for ApiErrorCode {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<ApiErrorCode> for i32 {
+    fn from(code: ApiErrorCode) -> Self {
+        code.code()
+    }
 }
 
 // TODO if response / response meta don't capture unknown fields then they should probably be set to fail on unknown fields
@@ -341,6 +433,64 @@ pub struct SuccessResponse<RT> {
     pub response: RT,
 }
 
+impl<RT: serde::de::DeserializeOwned> Response<RT> {
+    /// parse a response, like deserializing one directly would, but with a more useful error if
+    /// it doesn't match the expected shape.
+    ///
+    /// deserializing an (untagged) [`Response`] only reports that the data didn't match either
+    /// variant when parsing fails. this instead decides up front whether it's looking at a
+    /// failure or a success, so it can report which field (e.g. `response.content[2].media`)
+    /// couldn't be parsed and why.
+    ///
+    /// ```
+    /// use tumblr_api::api::{Response, CreatePostResponse};
+    /// let data = r#"{"meta":{"msg":"Created","status":201},"response":{"id":1234}}"#;
+    /// let err = Response::<CreatePostResponse>::from_json(data.as_bytes()).unwrap_err();
+    /// assert_eq!(err.path, "response.id");
+    /// ```
+    ///
+    /// # Errors
+    /// returns an error if `json` isn't valid json, or doesn't match the expected response shape
+    pub fn from_json(json: &[u8]) -> Result<Self, ResponseParseError> {
+        let value: serde_json::Value =
+            serde_json::from_slice(json).map_err(|source| ResponseParseError {
+                path: String::new(),
+                source,
+            })?;
+        Self::from_value(value)
+    }
+
+    /// like [`from_json`][Self::from_json], but for an already-parsed json value
+    ///
+    /// # Errors
+    /// returns an error if `value` doesn't match the expected response shape
+    pub fn from_value(value: serde_json::Value) -> Result<Self, ResponseParseError> {
+        // failures always have an `errors` list, and successes never do
+        let is_failure = value.get("errors").is_some();
+        let result = if is_failure {
+            serde_path_to_error::deserialize(value).map(Self::Failure)
+        } else {
+            serde_path_to_error::deserialize(value).map(Self::Success)
+        };
+        result.map_err(|err| ResponseParseError {
+            path: err.path().to_string(),
+            source: err.into_inner(),
+        })
+    }
+}
+
+/// an api response that couldn't be parsed, along with where in it the problem was.
+#[derive(Debug, thiserror::Error)]
+#[error("couldn't parse response at `{path}`: {source}")]
+pub struct ResponseParseError {
+    /// the path to the field that couldn't be parsed, e.g. `response.content[0].type`.
+    /// (`.` if the problem was with the response as a whole, and empty if it wasn't json at
+    /// all.)
+    pub path: String,
+    #[source]
+    pub source: serde_json::Error,
+}
+
 impl<RT> From<Response<RT>> for ResponseResult<RT> {
     fn from(val: Response<RT>) -> Self {
         match val {
@@ -373,7 +523,11 @@ pub struct ResponseError {
 impl fmt::Display for ResponseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fn fmt_error_entry(err: &ResponseErrorEntry, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "({}) {}", err.code, err.title)
+            write!(f, "({}) {}", err.code, err.title)?;
+            if let Some(detail) = &err.detail {
+                write!(f, ": {detail}")?;
+            }
+            Ok(())
         }
         match &self.errors[..] {
             [] => {
@@ -395,6 +549,14 @@ impl fmt::Display for ResponseError {
     }
 }
 
+impl ResponseError {
+    /// whether any of the errors has the given subcode
+    #[must_use]
+    pub fn has_error_code(&self, code: ApiErrorCode) -> bool {
+        self.errors.iter().any(|err| err.error_code() == code)
+    }
+}
+
 pub type ResponseResult<RT> = Result<SuccessResponse<RT>, ResponseError>;
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -446,6 +608,53 @@ pub struct UserInfoBlog {
     pub other_fields: serde_json::Map<String, serde_json::Value>,
 }
 
+/// <https://www.tumblr.com/docs/en/api/v2#info---retrieve-blog-info>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BlogInfoResponse {
+    pub blog: BlogInfo,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// a blog's avatar, see [`AvatarEndpoint`]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AvatarResponse {
+    /// where the avatar image is
+    pub avatar_url: String,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BlogInfo {
+    /// "The short blog name that appears before tumblr.com in a standard blog hostname"
+    pub name: String,
+    /// "The display title of the blog"
+    pub title: String,
+    /// "The blog's description"
+    pub description: String,
+    /// "The URL of the blog"
+    pub url: String,
+    /// "The unique identifier for the blog"
+    pub uuid: String,
+    /// "The time of the most recent post, in seconds since the epoch"
+    pub updated: i64,
+    /// "The total number of posts to this blog"
+    pub posts: i64,
+    /// "Indicates whether the blog allows questions"
+    pub ask: bool,
+    /// "Indicates whether the blog allows anonymous questions; returned only if ask is true"
+    pub ask_anon: Option<bool>,
+    /// "Number of likes for this user, returned only if this is the user's primary blog and
+    /// sharing of likes is enabled"
+    pub likes: Option<i64>,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
 // https://www.tumblr.com/docs/en/api/v2#posts---createreblog-a-post-neue-post-format
 // TODO should probably give this a builder again.
 //      (maybe gate `api`'s *Request builders behind an optional feature? we probably won't use them
@@ -518,6 +727,37 @@ pub struct CreatePostResponse {
     pub other_fields: serde_json::Map<String, serde_json::Value>,
 }
 
+// https://www.tumblr.com/docs/en/api/v2#posts---createreblog-a-post-neue-post-format
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReblogRequest {
+    /// "The unique public identifier of the Tumblelog that's being reblogged from"
+    pub parent_tumblelog_uuid: String,
+    // TODO - "intentionally a string instead of an integer for 32bit device compatibility", same as `CreatePostResponse::id`
+    /// "The unique public post ID being reblogged"
+    pub parent_post_id: String,
+    /// "The unique per-post hash validating that this is a genuine reblog action"
+    pub reblog_key: String,
+    /// "any content you want to add"
+    pub content: Vec<crate::npf::ContentBlock>,
+    /// "A comma-separated list of tags to associate with the post."
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
+    /// "The initial state of the new post, such as "published" or "queued"."
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<CreatePostState>,
+}
+
+/// the response to an action on an existing post, like deleting it or editing it
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PostIdResponse {
+    // TODO - same as `CreatePostResponse::id`, should make it an int
+    /// the id of the post
+    pub id: String,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct LimitsResponse {
     pub user: UserLimits,
@@ -555,5 +795,116 @@ pub struct LimitEntry {
 }
 
 impl LimitEntry {
-    // TODO add helpers for checking if the limit is done & checking if the reset has elapsed
+    /// whether `reset_at` has passed, i.e. whether `remaining` is out of date
+    #[must_use]
+    pub fn has_reset(&self) -> bool {
+        OffsetDateTime::now_utc() >= self.reset_at
+    }
+}
+
+/// pagination links included in some list responses, see
+/// <https://www.tumblr.com/docs/en/api/v2#_links---paginating-results>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Links {
+    /// where to get the next page of results, if there is one
+    pub next: Option<Link>,
+    /// where to get the previous page of results, if there is one
+    pub prev: Option<Link>,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Link {
+    /// "The path to use to make a request for the next page of results"
+    pub href: String,
+    /// "The HTTP method to use when making the request"
+    pub method: String,
+    /// "The query parameters to use when making the request"
+    #[serde(default)]
+    pub query_params: serde_json::Map<String, serde_json::Value>,
+}
+
+/// a page of posts, from an endpoint like [`BlogPostsEndpoint`] or [`DashboardEndpoint`]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PostsResponse {
+    pub posts: Vec<NPFPost>,
+    /// "The total number of post available for this request, useful for paginating through results"
+    /// (only included by some endpoints)
+    pub total_posts: Option<i64>,
+    #[serde(rename = "_links")]
+    pub links: Option<Links>,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#likes--retrieve-blogs-likes>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LikesResponse {
+    /// "An array of post objects (posts liked by the user)"
+    pub liked_posts: Vec<NPFPost>,
+    /// "Total number of liked posts"
+    pub liked_count: i64,
+    #[serde(rename = "_links")]
+    pub links: Option<Links>,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#followers--retrieve-a-blogs-followers>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FollowersResponse {
+    /// "The number of users currently following the blog"
+    pub total_users: i64,
+    pub users: Vec<Follower>,
+    #[serde(rename = "_links")]
+    pub links: Option<Links>,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Follower {
+    /// "The user's name on tumblr"
+    pub name: String,
+    /// "Whether the caller is following the user"
+    pub following: bool,
+    /// "The URL of the user's primary blog"
+    pub url: String,
+    /// "The time of the user's most recent post, in seconds since the epoch"
+    pub updated: i64,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#notes---get-notes-for-a-specific-post>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotesResponse {
+    pub notes: Vec<Note>,
+    /// "Total number of notes for the post"
+    pub total_notes: Option<i64>,
+    #[serde(rename = "_links")]
+    pub links: Option<Links>,
+    /// unknown/unhandled fields
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Note {
+    /// the kind of note, e.g. `like`, `reblog`, or `reply`
+    #[serde(rename = "type")]
+    pub note_type: String, // TODO enum
+    /// when the note was made, in seconds since the epoch
+    pub timestamp: i64,
+    /// the name of the blog that made the note
+    pub blog_name: String,
+    /// unknown/unhandled fields (which vary by the type of note)
+    #[serde(flatten)]
+    pub other_fields: serde_json::Map<String, serde_json::Value>,
 }
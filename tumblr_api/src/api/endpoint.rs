@@ -0,0 +1,810 @@
+//! describing api requests as data, independent of how they're sent.
+
+use std::{borrow::Cow, marker::PhantomData};
+
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+
+use super::{
+    multipart, AvatarResponse, BlogInfoResponse, CreatePostRequest, CreatePostResponse,
+    LimitsResponse, NPFPost, PostIdResponse, ReblogRequest, Response, ResponseError,
+    ResponseParseError, ResponseResult, SuccessResponse, UserInfoResponse,
+};
+
+/// how much of an unexpected response's body to include in the error
+const BODY_SNIPPET_LEN: usize = 512;
+
+/// a single api endpoint, along with the parameters for one request to it.
+///
+/// implementors just describe the request - turning that into an actual
+/// [`http::Request`] (via [`to_request`][Endpoint::to_request]) and parsing the
+/// [`http::Response`] that comes back (via [`parse_response`][Endpoint::parse_response]) are
+/// both done without any io, so you can send requests however you like.
+///
+/// ```
+/// use tumblr_api::api::{Endpoint, UserInfoEndpoint};
+/// # fn main() -> anyhow::Result<()> {
+/// let request = UserInfoEndpoint.to_request("https://api.tumblr.com/v2", Some("some token"))?;
+/// assert_eq!(request.uri(), "https://api.tumblr.com/v2/user/info");
+///
+/// // ...send `request` somehow, and get back:
+/// let response = http::Response::new(bytes::Bytes::from_static(
+///     br#"{"meta": {"status": 200, "msg": "OK"}, "response": {"user": {
+///         "following": 0, "default_post_format": "html", "name": "someone", "likes": 0,
+///         "blogs": []
+///     }}}"#,
+/// ));
+/// let user_info = UserInfoEndpoint::parse_response(&response)?.response;
+/// assert_eq!(user_info.user.name, "someone");
+/// # Ok(())
+/// # }
+/// ```
+pub trait Endpoint {
+    /// what a successful response to this endpoint contains
+    type Response: DeserializeOwned;
+
+    /// the endpoint's path relative to the api base url, with the parts that vary between
+    /// requests in braces, e.g. `blog/{blog_identifier}/posts`.
+    /// (for logging, metrics, etc., where the full path would be too specific.)
+    const PATH_TEMPLATE: &'static str;
+
+    fn method(&self) -> http::Method;
+
+    /// the path relative to the api base url, e.g. `blog/staff/posts`
+    fn path(&self) -> Cow<'_, str>;
+
+    /// the blog this request is about, if any. (only used for logging, not in building the
+    /// request.)
+    fn blog_identifier(&self) -> Option<&str> {
+        None
+    }
+
+    /// whether responses to this request can be stored in the client's response cache (see
+    /// [`ClientBuilder::cache`][crate::client::ClientBuilder::cache]) and reused for later
+    /// identical requests. only makes sense for `GET`s.
+    fn cacheable(&self) -> bool {
+        false
+    }
+
+    /// query string parameters
+    fn query(&self) -> Vec<(Cow<'static, str>, String)> {
+        Vec::new()
+    }
+
+    /// the request body, if there is one
+    ///
+    /// # Errors
+    /// returns an error if the body can't be serialized
+    fn body(&self) -> Result<Option<RequestBody>, EndpointError> {
+        Ok(None)
+    }
+
+    /// build the http request for this endpoint. `bearer_token` is sent as the request's
+    /// authorization, if given.
+    ///
+    /// # Errors
+    /// returns an error if the body can't be serialized, or the resulting request is invalid
+    /// (e.g. the url is malformed)
+    fn to_request(
+        &self,
+        api_base_url: &str,
+        bearer_token: Option<&str>,
+    ) -> Result<http::Request<Bytes>, EndpointError> {
+        let mut url = join_url(api_base_url, &self.path());
+        let query = self.query();
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(
+                &form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(query)
+                    .finish(),
+            );
+        }
+        let mut builder = http::Request::builder().method(self.method()).uri(url);
+        if let Some(token) = bearer_token {
+            builder = builder.header(http::header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = match self.body()? {
+            Some(RequestBody { content_type, data }) => {
+                builder = builder.header(http::header::CONTENT_TYPE, content_type);
+                data
+            }
+            None => Bytes::new(),
+        };
+        Ok(builder.body(body)?)
+    }
+
+    /// build the request for this endpoint like [`to_request`][Endpoint::to_request] does
+    /// (without any authorization), and describe it instead of returning it as-is
+    ///
+    /// # Errors
+    /// returns an error if the body can't be serialized, or the resulting request is invalid
+    fn prepare(&self, api_base_url: &str) -> Result<PreparedRequest, EndpointError> {
+        Ok(PreparedRequest::from_request(
+            &self.to_request(api_base_url, None)?,
+        ))
+    }
+
+    /// parse the response to a request made to this endpoint
+    ///
+    /// # Errors
+    /// returns an error if the api responded with an error, or if the response couldn't be
+    /// parsed
+    fn parse_response(
+        response: &http::Response<Bytes>,
+    ) -> Result<SuccessResponse<Self::Response>, EndpointError> {
+        // anything that isn't json (or is, but isn't an api response, if the request failed)
+        // most likely didn't come from the api itself, so shouldn't be reported as a parsing
+        // error
+        let Ok(json) = serde_json::from_slice::<serde_json::Value>(response.body()) else {
+            return Err(unexpected_response(response));
+        };
+        let parsed = match Response::<Self::Response>::from_value(json) {
+            Ok(parsed) => parsed,
+            Err(_) if !response.status().is_success() => return Err(unexpected_response(response)),
+            Err(err) => return Err(err.into()),
+        };
+        let result: ResponseResult<Self::Response> = parsed.into();
+        Ok(result?)
+    }
+}
+
+/// the body of a request, along with its `Content-Type`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestBody {
+    pub content_type: String,
+    pub data: Bytes,
+}
+
+impl RequestBody {
+    /// # Errors
+    /// returns an error if `value` can't be serialized
+    pub fn json<T: serde::Serialize>(value: &T) -> Result<Self, EndpointError> {
+        Ok(Self {
+            content_type: "application/json".into(),
+            data: serde_json::to_vec(value)
+                .map_err(EndpointError::Serializing)?
+                .into(),
+        })
+    }
+
+    /// a json body, or if there are any `attachments`, a multipart form with the json as its
+    /// `json` part and each attachment as another part (the way tumblr takes uploads)
+    ///
+    /// # Errors
+    /// returns an error if `value` can't be serialized, or an attachment's mime type is invalid
+    pub fn json_with_attachments<T: serde::Serialize>(
+        value: &T,
+        attachments: &[Attachment],
+    ) -> Result<Self, EndpointError> {
+        let json = Self::json(value)?;
+        if attachments.is_empty() {
+            return Ok(json);
+        }
+        let (content_type, data) = multipart::encode(multipart_parts(json, attachments)?);
+        Ok(Self {
+            content_type,
+            data: data.into(),
+        })
+    }
+
+    /// a form body (`application/x-www-form-urlencoded`)
+    #[must_use]
+    pub fn form<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(pairs: I) -> Self {
+        Self {
+            content_type: "application/x-www-form-urlencoded".into(),
+            data: form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish()
+                .into_bytes()
+                .into(),
+        }
+    }
+}
+
+/// the parts of a multipart form with `json` as its `json` part and each of `attachments` as
+/// another part
+pub(crate) fn multipart_parts(
+    json: RequestBody,
+    attachments: &[Attachment],
+) -> Result<Vec<multipart::Part>, EndpointError> {
+    let mut parts = vec![multipart::Part {
+        name: "json".into(),
+        file_name: None,
+        mime_type: json.content_type.into(),
+        data: json.data,
+    }];
+    for attachment in attachments {
+        check_mime_type(&attachment.mime_type)?;
+        parts.push(multipart::Part {
+            name: attachment.identifier.clone(),
+            file_name: Some(multipart::ATTACHMENT_FILE_NAME),
+            mime_type: attachment.mime_type.clone(),
+            data: attachment.data.clone(),
+        });
+    }
+    Ok(parts)
+}
+
+/// # Errors
+/// returns an error if `mime_type` can't be sent as an attachment's `Content-Type`
+pub(crate) fn check_mime_type(mime_type: &str) -> Result<(), EndpointError> {
+    if multipart::is_valid_mime_type(mime_type) {
+        Ok(())
+    } else {
+        Err(EndpointError::InvalidMimeType {
+            mime_type: mime_type.to_string(),
+        })
+    }
+}
+
+/// a description of a request, as it would be sent. see [`Endpoint::prepare`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedRequest {
+    pub method: http::Method,
+    /// the full url, including the query string
+    pub url: String,
+    pub body: Option<PreparedBody>,
+}
+
+/// the body of a [`PreparedRequest`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreparedBody {
+    Json(serde_json::Value),
+    /// an `application/x-www-form-urlencoded` form
+    Form(Vec<(String, String)>),
+    /// a `multipart/form-data` form, like a new post with attachments is sent as
+    Multipart {
+        /// the contents of the part named `json`
+        json: serde_json::Value,
+        /// the other parts, in the order they're sent
+        parts: Vec<PreparedPart>,
+    },
+    /// anything else (or a body that claims to be json but isn't)
+    Other {
+        content_type: String,
+        data: Vec<u8>,
+    },
+}
+
+/// one of the (non-json) parts of a [`PreparedBody::Multipart`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedPart {
+    pub name: String,
+    pub mime_type: String,
+    /// the length of the part's data, in bytes, if it's known before sending (it isn't for
+    /// attachments that are streamed)
+    pub len: Option<usize>,
+}
+
+impl PreparedRequest {
+    /// describe `request`. multipart bodies can't be taken apart again, so are described as
+    /// [`PreparedBody::Other`].
+    #[must_use]
+    pub fn from_request(request: &http::Request<Bytes>) -> Self {
+        let content_type = request
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        let body = content_type.map(|content_type| {
+            let data = request.body();
+            if content_type.starts_with("application/json") {
+                if let Ok(json) = serde_json::from_slice(data) {
+                    return PreparedBody::Json(json);
+                }
+            } else if content_type.starts_with("application/x-www-form-urlencoded") {
+                return PreparedBody::Form(form_urlencoded::parse(data).into_owned().collect());
+            }
+            PreparedBody::Other {
+                content_type: content_type.to_string(),
+                data: data.to_vec(),
+            }
+        });
+        Self {
+            method: request.method().clone(),
+            url: request.uri().to_string(),
+            body,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EndpointError {
+    #[error("couldn't serialize request body: {0}")]
+    Serializing(#[source] serde_json::Error),
+    #[error("invalid mime type {mime_type:?}")]
+    InvalidMimeType { mime_type: String },
+    /// the request couldn't be built, e.g. because the blog name made for an invalid url
+    #[error("invalid request: {0}")]
+    InvalidRequest(#[from] http::Error),
+    /// the response was something other than a json api response, e.g. an html error page from
+    /// a proxy, or an empty body
+    #[error("unexpected {status} response{}: {body_snippet:?}", content_type.as_deref().map(|content_type| format!(" ({content_type})")).unwrap_or_default())]
+    UnexpectedResponse {
+        status: http::StatusCode,
+        content_type: Option<String>,
+        /// the start of the response body
+        body_snippet: String,
+    },
+    /// the response was json, but didn't have the expected shape
+    #[error(transparent)]
+    Deserializing(#[from] ResponseParseError),
+    #[error(transparent)]
+    Api(#[from] ResponseError),
+}
+
+fn unexpected_response(response: &http::Response<Bytes>) -> EndpointError {
+    let body = String::from_utf8_lossy(response.body());
+    let mut end = body.len().min(BODY_SNIPPET_LEN);
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    EndpointError::UnexpectedResponse {
+        status: response.status(),
+        content_type: response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body_snippet: body[..end].to_string(),
+    }
+}
+
+/// `path` is relative to `base`, e.g. `"user/info"`
+fn join_url(base: &str, path: &str) -> String {
+    format!("{}/{path}", base.trim_end_matches('/'))
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#userinfo--get-a-users-information>
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserInfoEndpoint;
+
+impl Endpoint for UserInfoEndpoint {
+    type Response = UserInfoResponse;
+    const PATH_TEMPLATE: &'static str = "user/info";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        Self::PATH_TEMPLATE.into()
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#userlimits--get-a-users-limits>
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserLimitsEndpoint;
+
+impl Endpoint for UserLimitsEndpoint {
+    type Response = LimitsResponse;
+    const PATH_TEMPLATE: &'static str = "user/limits";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        Self::PATH_TEMPLATE.into()
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#info---retrieve-blog-info>
+#[derive(Debug, Clone)]
+pub struct BlogInfoEndpoint {
+    pub blog_identifier: String,
+}
+
+impl Endpoint for BlogInfoEndpoint {
+    type Response = BlogInfoResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/info";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/info", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+}
+
+/// a blog's avatar.
+///
+/// the api responds to this with a `301 Moved Permanently` redirecting to the image, whose body
+/// also has the image's url. that body is what gets parsed, so the redirect mustn't be followed.
+#[derive(Debug, Clone)]
+pub struct AvatarEndpoint {
+    pub blog_identifier: String,
+    /// the avatar's width (and height) in pixels: one of 16, 24, 30, 40, 48, 64, 96, 128 or 512.
+    /// the api defaults to 64.
+    pub size: Option<u16>,
+}
+
+impl Endpoint for AvatarEndpoint {
+    type Response = AvatarResponse;
+    // followed by `/{size}`, if there is one
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/avatar";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        match self.size {
+            Some(size) => format!("blog/{}/avatar/{size}", self.blog_identifier).into(),
+            None => format!("blog/{}/avatar", self.blog_identifier).into(),
+        }
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#postspost-id---fetching-a-post-neue-post-format>
+#[derive(Debug, Clone)]
+pub struct PostEndpoint {
+    pub blog_identifier: String,
+    pub post_id: i64,
+}
+
+impl Endpoint for PostEndpoint {
+    type Response = NPFPost;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/posts/{post_id}";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/posts/{}", self.blog_identifier, self.post_id).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+}
+
+/// a file uploaded along with a new post
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub data: Bytes,
+    pub mime_type: Box<str>,
+    /// what the post's content refers to this by, see
+    /// [`npf::MediaObjectContent::Identifier`][crate::npf::MediaObjectContent::Identifier]
+    pub identifier: Cow<'static, str>,
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#posts---createreblog-a-post-neue-post-format>
+#[derive(Debug)]
+pub struct CreatePostEndpoint {
+    pub blog_identifier: String,
+    pub request: CreatePostRequest,
+    /// if there are any attachments, the request is sent as a multipart form instead of as json
+    pub attachments: Vec<Attachment>,
+}
+
+impl Endpoint for CreatePostEndpoint {
+    type Response = CreatePostResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/posts";
+
+    fn method(&self) -> http::Method {
+        http::Method::POST
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/posts", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn prepare(&self, api_base_url: &str) -> Result<PreparedRequest, EndpointError> {
+        // build the real request first, so this fails in all the same ways sending would
+        let mut prepared = PreparedRequest::from_request(&self.to_request(api_base_url, None)?);
+        if !self.attachments.is_empty() {
+            prepared.body = Some(PreparedBody::Multipart {
+                json: serde_json::to_value(&self.request).map_err(EndpointError::Serializing)?,
+                parts: self
+                    .attachments
+                    .iter()
+                    .map(|attachment| PreparedPart {
+                        name: attachment.identifier.to_string(),
+                        mime_type: attachment.mime_type.to_string(),
+                        len: Some(attachment.data.len()),
+                    })
+                    .collect(),
+            });
+        }
+        Ok(prepared)
+    }
+
+    fn body(&self) -> Result<Option<RequestBody>, EndpointError> {
+        RequestBody::json_with_attachments(&self.request, &self.attachments).map(Some)
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#posts---createreblog-a-post-neue-post-format>
+#[derive(Debug)]
+pub struct ReblogEndpoint {
+    /// the blog to reblog the post to
+    pub blog_identifier: String,
+    pub request: ReblogRequest,
+}
+
+impl Endpoint for ReblogEndpoint {
+    type Response = CreatePostResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/posts";
+
+    fn method(&self) -> http::Method {
+        http::Method::POST
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/posts", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn body(&self) -> Result<Option<RequestBody>, EndpointError> {
+        RequestBody::json(&self.request).map(Some)
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#postspost-id---editing-a-post-neue-post-format>,
+/// changing just a post's tags.
+///
+/// editing a post replaces all of it, not just the parts that are sent, so the post's current
+/// content and layout have to be sent along with the new tags (e.g. from fetching it with
+/// [`PostEndpoint`] first), or the post is left empty.
+#[derive(Debug)]
+pub struct EditTagsEndpoint {
+    pub blog_identifier: String,
+    pub post_id: i64,
+    /// the post's current [`content`][NPFPost::content]
+    pub content: Vec<crate::npf::ContentBlock>,
+    /// the post's current [`layout`][NPFPost::layout]
+    pub layout: Vec<serde_json::Value>,
+    /// the post's new tags, comma-separated
+    pub tags: String,
+}
+
+impl Endpoint for EditTagsEndpoint {
+    type Response = PostIdResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/posts/{post_id}";
+
+    fn method(&self) -> http::Method {
+        http::Method::PUT
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/posts/{}", self.blog_identifier, self.post_id).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn body(&self) -> Result<Option<RequestBody>, EndpointError> {
+        RequestBody::json(&serde_json::json!({
+            "content": self.content,
+            "layout": self.layout,
+            "tags": self.tags,
+        }))
+        .map(Some)
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#postdelete--delete-a-post>
+#[derive(Debug, Clone)]
+pub struct DeletePostEndpoint {
+    pub blog_identifier: String,
+    pub post_id: i64,
+}
+
+impl Endpoint for DeletePostEndpoint {
+    type Response = PostIdResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/post/delete";
+
+    fn method(&self) -> http::Method {
+        http::Method::POST
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/post/delete", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn body(&self) -> Result<Option<RequestBody>, EndpointError> {
+        Ok(Some(RequestBody::form([(
+            "id",
+            self.post_id.to_string().as_str(),
+        )])))
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#userlike--like-a-post>
+#[derive(Debug, Clone)]
+pub struct LikeEndpoint {
+    pub post_id: i64,
+    /// the post's [`reblog_key`][NPFPost::reblog_key]
+    pub reblog_key: String,
+}
+
+impl Endpoint for LikeEndpoint {
+    /// (the api doesn't respond with anything in particular)
+    type Response = serde::de::IgnoredAny;
+    const PATH_TEMPLATE: &'static str = "user/like";
+
+    fn method(&self) -> http::Method {
+        http::Method::POST
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        Self::PATH_TEMPLATE.into()
+    }
+
+    fn body(&self) -> Result<Option<RequestBody>, EndpointError> {
+        Ok(Some(RequestBody::form([
+            ("id", self.post_id.to_string().as_str()),
+            ("reblog_key", &self.reblog_key),
+        ])))
+    }
+}
+
+/// any endpoint, for the ones this crate doesn't have a dedicated type for yet. the response
+/// is parsed as `T`, which can be [`serde_json::Value`] to take whatever the api sends.
+pub struct RawEndpoint<T> {
+    pub method: http::Method,
+    /// the path relative to the api base url, e.g. `blog/staff/followers`. a leading `/` or
+    /// `/v2/` is left off, so links the api gives out (like `_links.next.href`) can be used as-is,
+    /// and a query string is sent along with `query`.
+    pub path: String,
+    pub query: Vec<(Cow<'static, str>, String)>,
+    pub body: Option<RequestBody>,
+    /// (just marks what the response is parsed as)
+    pub response: PhantomData<fn() -> T>,
+}
+
+impl<T> RawEndpoint<T> {
+    /// a request to `path` with no query parameters or body
+    #[must_use]
+    pub fn new<P: Into<String>>(method: http::Method, path: P) -> Self {
+        Self {
+            method,
+            path: path.into(),
+            query: Vec::new(),
+            body: None,
+            response: PhantomData,
+        }
+    }
+
+    /// `path` without a leading `/` or api version, and its query string, if it has one
+    fn split_path(&self) -> (&str, Option<&str>) {
+        let path = self.path.trim_start_matches('/');
+        let path = path.strip_prefix("v2/").unwrap_or(path);
+        match path.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (path, None),
+        }
+    }
+}
+
+// (by hand rather than derived, so `T` doesn't need to be `Debug`)
+impl<T> std::fmt::Debug for RawEndpoint<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawEndpoint")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("query", &self.query)
+            .field("body", &self.body)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: DeserializeOwned> Endpoint for RawEndpoint<T> {
+    type Response = T;
+    const PATH_TEMPLATE: &'static str = "{path}";
+
+    fn method(&self) -> http::Method {
+        self.method.clone()
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        self.split_path().0.into()
+    }
+
+    fn query(&self) -> Vec<(Cow<'static, str>, String)> {
+        let mut query: Vec<_> = self
+            .split_path()
+            .1
+            .map(|query| {
+                form_urlencoded::parse(query.as_bytes())
+                    .map(|(key, value)| (key.into_owned().into(), value.into_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        query.extend(self.query.iter().cloned());
+        query
+    }
+
+    fn body(&self) -> Result<Option<RequestBody>, EndpointError> {
+        Ok(self.body.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_joining() {
+        assert_eq!(
+            join_url("https://api.tumblr.com/v2", "user/info"),
+            "https://api.tumblr.com/v2/user/info"
+        );
+        assert_eq!(
+            join_url("http://localhost:1234/v2/", "blog/foo/posts"),
+            "http://localhost:1234/v2/blog/foo/posts"
+        );
+    }
+
+    #[test]
+    fn test_raw_path() {
+        let endpoint = RawEndpoint::<serde_json::Value> {
+            query: vec![("limit".into(), "5".into())],
+            ..RawEndpoint::new(
+                http::Method::GET,
+                "/v2/blog/staff/posts?page_number=a%2Bb&tag=x",
+            )
+        };
+        let request = endpoint
+            .to_request("https://api.tumblr.com/v2", None)
+            .unwrap();
+        assert_eq!(
+            request.uri(),
+            "https://api.tumblr.com/v2/blog/staff/posts?page_number=a%2Bb&tag=x&limit=5"
+        );
+        for path in ["blog/staff/info", "/blog/staff/info", "/v2/blog/staff/info"] {
+            let endpoint = RawEndpoint::<serde_json::Value>::new(http::Method::GET, path);
+            assert_eq!(endpoint.path(), "blog/staff/info");
+            assert!(endpoint.query().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_unexpected_response() {
+        let response = http::Response::builder()
+            .status(502)
+            .header(http::header::CONTENT_TYPE, "text/html")
+            .body(Bytes::from_static(b"<html>bad gateway</html>"))
+            .unwrap();
+        assert!(matches!(
+            UserInfoEndpoint::parse_response(&response),
+            Err(EndpointError::UnexpectedResponse { status, content_type: Some(content_type), .. })
+                if status == 502 && content_type == "text/html"
+        ));
+    }
+}
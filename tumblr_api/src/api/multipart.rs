@@ -0,0 +1,134 @@
+//! building `multipart/form-data` bodies, for uploading attachments alongside a post.
+
+use bytes::Bytes;
+use std::{
+    borrow::Cow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+pub(crate) struct Part {
+    pub(crate) name: Cow<'static, str>,
+    pub(crate) file_name: Option<&'static str>,
+    pub(crate) mime_type: Box<str>,
+    pub(crate) data: Bytes,
+}
+
+/// tumblr requires attachments to have a filename but doesn't actually check it, so they all get
+/// this one
+pub(crate) const ATTACHMENT_FILE_NAME: &str = "a";
+
+/// whether `mime_type` can be put in a `Content-Type` header as-is
+pub(crate) fn is_valid_mime_type(mime_type: &str) -> bool {
+    let Some((kind, subtype)) = mime_type.split(';').next().and_then(|s| s.split_once('/')) else {
+        return false;
+    };
+    !kind.trim().is_empty()
+        && !subtype.trim().is_empty()
+        && mime_type
+            .chars()
+            .all(|c| c.is_ascii() && !c.is_ascii_control())
+}
+
+/// a part name or file name, escaped for use inside a quoted header parameter
+fn quote(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
+
+/// a boundary that's vanishingly unlikely to show up in any of the parts
+pub(crate) fn random_boundary() -> String {
+    let state = RandomState::new();
+    let a = state.build_hasher().finish();
+    let mut hasher = state.build_hasher();
+    hasher.write_u64(a);
+    format!("{a:016x}{:016x}", hasher.finish())
+}
+
+/// the `Content-Type` of a multipart form delimited by `boundary`
+pub(crate) fn content_type(boundary: &str) -> String {
+    format!("multipart/form-data; boundary={boundary}")
+}
+
+/// what goes before a part's data
+pub(crate) fn part_headers(
+    boundary: &str,
+    name: &str,
+    file_name: Option<&str>,
+    mime_type: &str,
+) -> String {
+    let file_name = file_name
+        .map(|file_name| format!("; filename=\"{}\"", quote(file_name)))
+        .unwrap_or_default();
+    format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"{file_name}\r\nContent-Type: {mime_type}\r\n\r\n",
+        quote(name),
+    )
+}
+
+/// what goes after a part's data
+pub(crate) const PART_END: &[u8] = b"\r\n";
+
+/// what goes after the last part
+pub(crate) fn closing_delimiter(boundary: &str) -> String {
+    format!("--{boundary}--\r\n")
+}
+
+/// encode `parts` as a multipart form, returning the `Content-Type` to send it with and the body
+pub(super) fn encode(parts: Vec<Part>) -> (String, Vec<u8>) {
+    let boundary = random_boundary();
+    let mut body = Vec::new();
+    for part in parts {
+        body.extend_from_slice(
+            part_headers(&boundary, &part.name, part.file_name, &part.mime_type).as_bytes(),
+        );
+        body.extend_from_slice(&part.data);
+        body.extend_from_slice(PART_END);
+    }
+    body.extend_from_slice(closing_delimiter(&boundary).as_bytes());
+    (content_type(&boundary), body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let (content_type, body) = encode(vec![
+            Part {
+                name: "json".into(),
+                file_name: None,
+                mime_type: "application/json".into(),
+                data: Bytes::from_static(b"{}"),
+            },
+            Part {
+                name: "my \"image\"".into(),
+                file_name: Some("a"),
+                mime_type: "image/png".into(),
+                data: Bytes::from_static(&[1, 2, 3]),
+            },
+        ]);
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let mut expected = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"json\"\r\nContent-Type: application/json\r\n\r\n{{}}\r\n\
+             --{boundary}\r\nContent-Disposition: form-data; name=\"my \\\"image\\\"\"; filename=\"a\"\r\nContent-Type: image/png\r\n\r\n"
+        )
+        .into_bytes();
+        expected.extend_from_slice(&[1, 2, 3]);
+        expected.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn test_mime_type_validation() {
+        assert!(is_valid_mime_type("image/png"));
+        assert!(is_valid_mime_type("text/plain; charset=utf-8"));
+        assert!(!is_valid_mime_type("png"));
+        assert!(!is_valid_mime_type("image/"));
+        assert!(!is_valid_mime_type("image/png\r\nX-Injected: yes"));
+    }
+}
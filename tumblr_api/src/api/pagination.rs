@@ -0,0 +1,579 @@
+//! endpoints that return their results a page at a time.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Endpoint, Follower, FollowersResponse, LikesResponse, Link, Links, NPFPost, Note,
+    NotesResponse, PostsResponse,
+};
+
+/// query parameters, as returned by [`Endpoint::query`]
+type Query = Vec<(Cow<'static, str>, String)>;
+
+/// a list endpoint, whose results are split across multiple pages.
+///
+/// [`Client::paginate`][crate::client::Client::paginate] turns one of these into a stream of
+/// items, fetching each page as it's needed.
+pub trait Paginated: Endpoint {
+    /// what each page is a list of
+    type Item;
+
+    /// request the page at `cursor` instead of the first page
+    fn set_cursor(&mut self, cursor: Option<Cursor>);
+
+    /// split a response from this endpoint into its items, and the cursor for the page after it
+    /// (`None` if this is the last page)
+    fn to_page(&self, response: Self::Response) -> Page<Self::Item>;
+
+    /// the cursor for partway through a page this endpoint returned, just after `last`, which
+    /// was its `taken`th item. used when a [`Paginator`][crate::client::Paginator]'s limit cuts
+    /// a page short, so resuming from it doesn't skip the rest of the page. `None` if there's no
+    /// way to resume there.
+    fn cursor_within_page(&self, _taken: usize, _last: &Self::Item) -> Option<Cursor> {
+        None
+    }
+}
+
+/// one page of results from a [`Paginated`] endpoint
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// where the next page starts, if there is one. save this to resume from the next page later.
+    pub next: Option<Cursor>,
+}
+
+/// a position in a list of results, i.e. the query parameters to request a particular page with.
+///
+/// depending on the endpoint, this is an offset, a timestamp or post id to get results from
+/// before, or whatever the api's `_links.next` said to use. it can be (de)serialized, so e.g. a
+/// long-running archive job can save where it was and pick up from there later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    params: Vec<(String, String)>,
+}
+
+impl Cursor {
+    #[must_use]
+    pub fn from_query_params(params: Vec<(String, String)>) -> Self {
+        Self { params }
+    }
+
+    #[must_use]
+    pub fn query_params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn single(name: &str, value: String) -> Self {
+        Self {
+            params: vec![(name.to_string(), value)],
+        }
+    }
+
+    fn from_link(link: &Link) -> Self {
+        Self {
+            params: link
+                .query_params
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        }
+    }
+
+    /// `query`, with any parameters set by `cursor` replaced by the cursor's values
+    fn apply(cursor: Option<&Self>, mut query: Query) -> Query {
+        if let Some(cursor) = cursor {
+            query.retain(|(key, _)| cursor.param(key).is_none());
+            query.extend(
+                cursor
+                    .params
+                    .iter()
+                    .map(|(key, value)| (Cow::Owned(key.clone()), value.clone())),
+            );
+        }
+        query
+    }
+
+    /// the cursor for the page after one requested with `cursor`, for endpoints paginated by
+    /// offset. `total` is the total number of results, if the api says.
+    fn next_offset(cursor: Option<&Self>, count: usize, total: Option<i64>) -> Option<Self> {
+        let offset = cursor
+            .and_then(|cursor| cursor.param("offset")?.parse::<u64>().ok())
+            .unwrap_or(0)
+            + u64::try_from(count).ok()?;
+        if count == 0
+            || total.is_some_and(|total| i64::try_from(offset).unwrap_or(i64::MAX) >= total)
+        {
+            return None;
+        }
+        Some(Self::single("offset", offset.to_string()))
+    }
+
+    /// the cursor for partway through a page requested with `cursor`, after its first `taken`
+    /// items, for endpoints paginated by offset. a cursor from `_links` that's by something else
+    /// (e.g. a `before` timestamp) can't be offset into, so that whole page is requested again
+    /// instead - repeating the items before, rather than skipping the ones after.
+    fn offset_within_page(cursor: Option<&Self>, taken: usize) -> Self {
+        let mut params = cursor
+            .map(|cursor| cursor.params.clone())
+            .unwrap_or_default();
+        if params
+            .iter()
+            .any(|(key, _)| key == "before" || key == "after")
+        {
+            return Self { params };
+        }
+        let offset = cursor
+            .and_then(|cursor| cursor.param("offset")?.parse::<u64>().ok())
+            .unwrap_or(0)
+            + u64::try_from(taken).unwrap_or(u64::MAX);
+        params.retain(|(key, _)| key != "offset");
+        params.push(("offset".into(), offset.to_string()));
+        Self { params }
+    }
+
+    /// the cursor for the page after one with `count` items: from the response's `_links` if it
+    /// had any, otherwise from `fallback`
+    fn next(
+        links: Option<&Links>,
+        count: usize,
+        fallback: impl FnOnce() -> Option<Self>,
+    ) -> Option<Self> {
+        // (an empty page is always the last, to guard against being sent around in circles)
+        if count == 0 {
+            return None;
+        }
+        match links {
+            Some(links) => links.next.as_ref().map(Self::from_link),
+            None => fallback(),
+        }
+    }
+}
+
+/// common query parameters for endpoints that return posts
+fn posts_query(page_size: Option<u32>) -> Query {
+    let mut query: Query = vec![("npf".into(), "true".into())];
+    if let Some(page_size) = page_size {
+        query.push(("limit".into(), page_size.to_string()));
+    }
+    query
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#posts--retrieve-published-posts>
+#[derive(Debug, Clone, Default)]
+pub struct BlogPostsEndpoint {
+    pub blog_identifier: String,
+    /// only return posts with this tag
+    pub tag: Option<String>,
+    /// how many posts to request per page (the api allows up to 20)
+    pub page_size: Option<u32>,
+    pub cursor: Option<Cursor>,
+}
+
+impl Endpoint for BlogPostsEndpoint {
+    type Response = PostsResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/posts";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/posts", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn query(&self) -> Query {
+        let mut query = posts_query(self.page_size);
+        if let Some(tag) = &self.tag {
+            query.push(("tag".into(), tag.clone()));
+        }
+        Cursor::apply(self.cursor.as_ref(), query)
+    }
+}
+
+impl Paginated for BlogPostsEndpoint {
+    type Item = NPFPost;
+
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+    }
+
+    fn to_page(&self, response: PostsResponse) -> Page<NPFPost> {
+        let count = response.posts.len();
+        let next = Cursor::next(response.links.as_ref(), count, || {
+            Cursor::next_offset(self.cursor.as_ref(), count, response.total_posts)
+        });
+        Page {
+            items: response.posts,
+            next,
+        }
+    }
+
+    fn cursor_within_page(&self, taken: usize, _last: &Self::Item) -> Option<Cursor> {
+        Some(Cursor::offset_within_page(self.cursor.as_ref(), taken))
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#postsdraft--retrieve-draft-posts>
+#[derive(Debug, Clone, Default)]
+pub struct DraftsEndpoint {
+    pub blog_identifier: String,
+    pub cursor: Option<Cursor>,
+}
+
+impl Endpoint for DraftsEndpoint {
+    type Response = PostsResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/posts/draft";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/posts/draft", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn query(&self) -> Query {
+        Cursor::apply(self.cursor.as_ref(), posts_query(None))
+    }
+}
+
+impl Paginated for DraftsEndpoint {
+    type Item = NPFPost;
+
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+    }
+
+    fn to_page(&self, response: PostsResponse) -> Page<NPFPost> {
+        let count = response.posts.len();
+        // drafts are paged through by asking for the ones before the oldest we've seen
+        let next = Cursor::next(response.links.as_ref(), count, || {
+            response
+                .posts
+                .last()
+                .map(|post| Cursor::single("before_id", post.id.to_string()))
+        });
+        Page {
+            items: response.posts,
+            next,
+        }
+    }
+
+    fn cursor_within_page(&self, _taken: usize, last: &NPFPost) -> Option<Cursor> {
+        Some(Cursor::single("before_id", last.id.to_string()))
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#likes--retrieve-blogs-likes>
+#[derive(Debug, Clone, Default)]
+pub struct BlogLikesEndpoint {
+    pub blog_identifier: String,
+    /// how many posts to request per page (the api allows up to 20)
+    pub page_size: Option<u32>,
+    pub cursor: Option<Cursor>,
+}
+
+impl Endpoint for BlogLikesEndpoint {
+    type Response = LikesResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/likes";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/likes", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn query(&self) -> Query {
+        Cursor::apply(self.cursor.as_ref(), posts_query(self.page_size))
+    }
+}
+
+impl Paginated for BlogLikesEndpoint {
+    type Item = NPFPost;
+
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+    }
+
+    fn to_page(&self, response: LikesResponse) -> Page<NPFPost> {
+        let count = response.liked_posts.len();
+        let next = Cursor::next(response.links.as_ref(), count, || {
+            Cursor::next_offset(self.cursor.as_ref(), count, Some(response.liked_count))
+        });
+        Page {
+            items: response.liked_posts,
+            next,
+        }
+    }
+
+    fn cursor_within_page(&self, taken: usize, _last: &Self::Item) -> Option<Cursor> {
+        Some(Cursor::offset_within_page(self.cursor.as_ref(), taken))
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#followers--retrieve-a-blogs-followers>
+#[derive(Debug, Clone, Default)]
+pub struct FollowersEndpoint {
+    pub blog_identifier: String,
+    /// how many users to request per page (the api allows up to 20)
+    pub page_size: Option<u32>,
+    pub cursor: Option<Cursor>,
+}
+
+impl Endpoint for FollowersEndpoint {
+    type Response = FollowersResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/followers";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/followers", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn query(&self) -> Query {
+        let mut query = Query::new();
+        if let Some(page_size) = self.page_size {
+            query.push(("limit".into(), page_size.to_string()));
+        }
+        Cursor::apply(self.cursor.as_ref(), query)
+    }
+}
+
+impl Paginated for FollowersEndpoint {
+    type Item = Follower;
+
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+    }
+
+    fn to_page(&self, response: FollowersResponse) -> Page<Follower> {
+        let count = response.users.len();
+        let next = Cursor::next(response.links.as_ref(), count, || {
+            Cursor::next_offset(self.cursor.as_ref(), count, Some(response.total_users))
+        });
+        Page {
+            items: response.users,
+            next,
+        }
+    }
+
+    fn cursor_within_page(&self, taken: usize, _last: &Self::Item) -> Option<Cursor> {
+        Some(Cursor::offset_within_page(self.cursor.as_ref(), taken))
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#userdashboard--retrieve-a-users-dashboard>
+#[derive(Debug, Clone, Default)]
+pub struct DashboardEndpoint {
+    /// only return posts newer than this post id
+    pub since_id: Option<i64>,
+    /// how many posts to request per page (the api allows up to 20)
+    pub page_size: Option<u32>,
+    pub cursor: Option<Cursor>,
+}
+
+impl Endpoint for DashboardEndpoint {
+    type Response = PostsResponse;
+    const PATH_TEMPLATE: &'static str = "user/dashboard";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        Self::PATH_TEMPLATE.into()
+    }
+
+    fn query(&self) -> Query {
+        let mut query = posts_query(self.page_size);
+        if let Some(since_id) = self.since_id {
+            query.push(("since_id".into(), since_id.to_string()));
+        }
+        Cursor::apply(self.cursor.as_ref(), query)
+    }
+}
+
+impl Paginated for DashboardEndpoint {
+    type Item = NPFPost;
+
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+    }
+
+    fn to_page(&self, response: PostsResponse) -> Page<NPFPost> {
+        let count = response.posts.len();
+        let next = Cursor::next(response.links.as_ref(), count, || {
+            Cursor::next_offset(self.cursor.as_ref(), count, response.total_posts)
+        });
+        Page {
+            items: response.posts,
+            next,
+        }
+    }
+
+    fn cursor_within_page(&self, taken: usize, _last: &Self::Item) -> Option<Cursor> {
+        Some(Cursor::offset_within_page(self.cursor.as_ref(), taken))
+    }
+}
+
+/// <https://www.tumblr.com/docs/en/api/v2#notes---get-notes-for-a-specific-post>
+#[derive(Debug, Clone, Default)]
+pub struct NotesEndpoint {
+    pub blog_identifier: String,
+    pub post_id: i64,
+    /// which notes to return, e.g. `all`, `likes`, `conversation`, `rollup`, or
+    /// `reblogs_with_tags`
+    pub mode: Option<String>,
+    pub cursor: Option<Cursor>,
+}
+
+impl Endpoint for NotesEndpoint {
+    type Response = NotesResponse;
+    const PATH_TEMPLATE: &'static str = "blog/{blog_identifier}/notes";
+
+    fn method(&self) -> http::Method {
+        http::Method::GET
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        format!("blog/{}/notes", self.blog_identifier).into()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        Some(&self.blog_identifier)
+    }
+
+    fn query(&self) -> Query {
+        let mut query: Query = vec![("id".into(), self.post_id.to_string())];
+        if let Some(mode) = &self.mode {
+            query.push(("mode".into(), mode.clone()));
+        }
+        Cursor::apply(self.cursor.as_ref(), query)
+    }
+}
+
+impl Paginated for NotesEndpoint {
+    type Item = Note;
+
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+    }
+
+    fn to_page(&self, response: NotesResponse) -> Page<Note> {
+        let count = response.notes.len();
+        // notes are newest first, so the next page is the ones before the last we got
+        let next = Cursor::next(response.links.as_ref(), count, || {
+            response
+                .notes
+                .last()
+                .map(|note| Cursor::single("before_timestamp", note.timestamp.to_string()))
+        });
+        Page {
+            items: response.notes,
+            next,
+        }
+    }
+
+    fn cursor_within_page(&self, _taken: usize, last: &Note) -> Option<Cursor> {
+        Some(Cursor::single(
+            "before_timestamp",
+            last.timestamp.to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_overrides_query() {
+        let endpoint = BlogPostsEndpoint {
+            blog_identifier: "staff".into(),
+            page_size: Some(5),
+            cursor: Some(Cursor::from_query_params(vec![
+                ("limit".into(), "10".into()),
+                ("offset".into(), "10".into()),
+            ])),
+            ..Default::default()
+        };
+        let request = endpoint
+            .to_request("https://api.tumblr.com/v2", None)
+            .unwrap();
+        assert_eq!(
+            request.uri(),
+            "https://api.tumblr.com/v2/blog/staff/posts?npf=true&limit=10&offset=10"
+        );
+    }
+
+    #[test]
+    fn test_next_offset() {
+        assert_eq!(
+            Cursor::next_offset(None, 20, None),
+            Some(Cursor::single("offset", "20".into()))
+        );
+        let cursor = Cursor::single("offset", "20".into());
+        assert_eq!(
+            Cursor::next_offset(Some(&cursor), 20, None),
+            Some(Cursor::single("offset", "40".into()))
+        );
+        assert_eq!(Cursor::next_offset(Some(&cursor), 20, Some(40)), None);
+        assert_eq!(Cursor::next_offset(Some(&cursor), 0, None), None);
+    }
+
+    #[test]
+    fn test_offset_within_page() {
+        assert_eq!(
+            Cursor::offset_within_page(None, 3),
+            Cursor::single("offset", "3".into())
+        );
+        let cursor = Cursor::from_query_params(vec![
+            ("offset".into(), "20".into()),
+            ("limit".into(), "20".into()),
+        ]);
+        assert_eq!(
+            Cursor::offset_within_page(Some(&cursor), 3),
+            Cursor::from_query_params(vec![
+                ("limit".into(), "20".into()),
+                ("offset".into(), "23".into()),
+            ])
+        );
+        let cursor = Cursor::single("before", "1700000000".into());
+        assert_eq!(Cursor::offset_within_page(Some(&cursor), 3), cursor);
+    }
+}
@@ -17,6 +17,7 @@
 //! # #[tokio::main]
 //! # async fn main() -> anyhow::Result<()> {
 //! # let credentials = Credentials::new("your consumer key", "your consumer secret");
+//! // or any other `tumblr_api::backend::HttpBackend`
 //! let reqwest_client = reqwest::Client::new();
 //! let token = credentials.authorize(&reqwest_client).await?;
 //! # Ok(())
@@ -34,14 +35,22 @@
 
 use std::{
     fmt,
+    sync::{Arc, Mutex, PoisonError},
     time::{Duration, Instant},
 };
 
+use futures_util::future::Shared;
+
 use serde::{Deserialize, Serialize};
 use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
 use serde_with::{serde_as, DurationSeconds};
 use veil::Redact;
 
+use crate::backend::{BoxFuture, HttpBackend, TransportError};
+
+/// the token endpoint used by [`Credentials::authorize`]
+pub const DEFAULT_TOKEN_URL: &str = "https://api.tumblr.com/v2/oauth2/token";
+
 /// API credentials, which can be used to acquire an access token
 ///
 /// [`Credentials`]'s [`Debug`][`fmt::Debug`] implementation will be redacted (via [`veil`]), so
@@ -52,7 +61,35 @@ pub struct Credentials {
     consumer_key: String,
     #[redact]
     consumer_secret: String,
-    token: async_lock::Mutex<Option<TokenWithExpiry>>,
+    token: Arc<TokenCache>,
+}
+
+/// the most recent token, and the request for a new one if there's one in progress
+struct TokenCache {
+    state: Mutex<TokenState>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    token: Option<TokenWithExpiry>,
+    /// a token request that anyone who needs a token can wait on, see
+    /// [`Credentials::authorize_shared`]
+    in_flight: Option<SharedRefresh>,
+}
+
+/// a token request that keeps going even if whoever started it stops waiting for it
+type SharedRefresh = Shared<BoxFuture<'static, Result<TokenWithExpiry, Arc<Error>>>>;
+
+impl TokenCache {
+    fn state(&self) -> std::sync::MutexGuard<'_, TokenState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for TokenCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenCache").finish_non_exhaustive()
+    }
 }
 
 /// access token for the API
@@ -79,22 +116,25 @@ impl fmt::Display for BearerToken {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct TokenWithExpiry {
     token: BearerToken,
     /// when the token will expire
     expires_at: Instant,
 }
 
+/// how long before its actual expiry a token is treated as expired, so that a token that's about
+/// to expire isn't handed out only to be rejected by the time the request using it arrives
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
 impl TokenWithExpiry {
     fn is_expired(&self) -> bool {
-        let now = Instant::now();
-        // TODO this should probably be done with some extra tolerance, if we're within a
-        //      couple seconds of expiring we should probably just count it
-        now >= self.expires_at
+        Instant::now() + EXPIRY_MARGIN >= self.expires_at
     }
 }
 
+// TODO use this for the `scope` in the token request form data
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 enum Scope {
@@ -143,7 +183,14 @@ enum OAuth2AuthResponse {
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error(transparent)]
-    Network(#[from] reqwest::Error),
+    Network(#[from] TransportError),
+    /// the token endpoint's response couldn't be parsed
+    #[error("malformed token response ({status}): {source}")]
+    Deserializing {
+        status: http::StatusCode,
+        #[source]
+        source: serde_json::Error,
+    },
     // TODO give this a better message format instead of just :?ing the `Option<String>`s
     #[error("oauth error! {error} - {error_description:?} - {error_uri:?}")]
     OAuth {
@@ -151,6 +198,16 @@ pub enum Error {
         error_description: Option<String>,
         error_uri: Option<String>,
     },
+    /// a token request that several callers were waiting on failed
+    #[error(transparent)]
+    Shared(Arc<Error>),
+}
+
+impl Error {
+    /// unwrap the error from a shared token request, if nothing else is still holding onto it
+    fn unshare(err: Arc<Self>) -> Self {
+        Arc::try_unwrap(err).unwrap_or_else(Self::Shared)
+    }
 }
 
 impl Credentials {
@@ -162,29 +219,56 @@ impl Credentials {
         Self {
             consumer_key: consumer_key.into(),
             consumer_secret: consumer_secret.into(),
-            token: None.into(),
+            token: Arc::new(TokenCache {
+                state: Mutex::default(),
+            }),
         }
     }
 
-    async fn definitely_authorize(
-        &self,
-        http_client: &reqwest::Client,
+    /// an identifier for these credentials that doesn't give away the keys themselves, e.g. to
+    /// keep responses cached for different apps apart.
+    ///
+    /// this ends up in keys stored outside the process (see
+    /// [`CacheStore`][crate::client::cache::CacheStore]), so it's an fnv-1a hash, which unlike
+    /// [`DefaultHasher`][std::collections::hash_map::DefaultHasher] won't change between rust
+    /// versions.
+    #[cfg(feature = "client")]
+    pub(crate) fn fingerprint(&self) -> String {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        let hash = self.consumer_key.bytes().fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+        format!("{hash:016x}")
+    }
+
+    async fn definitely_authorize<B: HttpBackend + ?Sized>(
+        consumer_key: &str,
+        consumer_secret: &str,
+        backend: &B,
+        token_url: &str,
     ) -> Result<TokenWithExpiry, Error> {
         let request_sent_at = Instant::now();
         // TODO make a proper serde struct for this rather than doing it this way
-        let form_data = [
-            ("grant_type", "client_credentials"),
-            ("scope", "basic offline_access write"),
-            ("client_id", &self.consumer_key),
-            ("client_secret", &self.consumer_secret),
-        ];
-        let resp: OAuth2AuthResponse = http_client
-            .post("https://api.tumblr.com/v2/oauth2/token")
-            .form(&form_data)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let form_data = form_urlencoded::Serializer::new(String::new())
+            .append_pair("grant_type", "client_credentials")
+            .append_pair("scope", "basic offline_access write")
+            .append_pair("client_id", consumer_key)
+            .append_pair("client_secret", consumer_secret)
+            .finish();
+        let request = http::Request::post(token_url)
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(form_data.into_bytes().into())
+            .map_err(|err| TransportError::new(crate::backend::TransportErrorKind::Other, err))?;
+        let response = backend.send(request).await?;
+        let resp: OAuth2AuthResponse =
+            serde_json::from_slice(response.body()).map_err(|source| Error::Deserializing {
+                status: response.status(),
+                source,
+            })?;
         match resp {
             OAuth2AuthResponse::Token {
                 access_token,
@@ -208,27 +292,125 @@ impl Credentials {
         }
     }
 
+    /// [`definitely_authorize`][Self::definitely_authorize], in a span if tracing is enabled.
+    /// (the span deliberately doesn't include the keys, to keep them out of logs.)
+    async fn refresh<B: HttpBackend + ?Sized>(
+        consumer_key: &str,
+        consumer_secret: &str,
+        backend: &B,
+        token_url: &str,
+    ) -> Result<TokenWithExpiry, Error> {
+        let future = Self::definitely_authorize(consumer_key, consumer_secret, backend, token_url);
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(
+            future,
+            tracing::info_span!("tumblr_api.authorize", token_url),
+        );
+        let result = future.await;
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(token) => tracing::debug!(
+                expires_in = ?token.expires_at.saturating_duration_since(Instant::now()),
+                "got new token"
+            ),
+            Err(err) => tracing::debug!(error = %err, "authorizing failed"),
+        }
+        result
+    }
+
     /// returns an active token, authorizing if we haven't already done so or if the currently
     /// stored token has expired
-    pub async fn authorize(&self, http_client: &reqwest::Client) -> Result<BearerToken, Error> {
-        let mut guard = self.token.lock().await;
-        match &*guard {
-            None => {
-                let new_token = self.definitely_authorize(http_client).await?;
-                let ret = new_token.token.clone();
-                *guard = Some(new_token);
-                Ok(ret)
+    ///
+    /// # Errors
+    /// returns an error if the token request fails or the API rejects the credentials
+    pub async fn authorize<B: HttpBackend + Clone + 'static>(
+        &self,
+        backend: &B,
+    ) -> Result<BearerToken, Error> {
+        self.authorize_with_token_url(backend, DEFAULT_TOKEN_URL)
+            .await
+    }
+
+    /// same as [`Credentials::authorize`], but requests new tokens from `token_url` instead of
+    /// [`DEFAULT_TOKEN_URL`]
+    ///
+    /// # Errors
+    /// returns an error if the token request fails or the API rejects the credentials
+    pub async fn authorize_with_token_url<B: HttpBackend + Clone + 'static>(
+        &self,
+        backend: &B,
+        token_url: &str,
+    ) -> Result<BearerToken, Error> {
+        self.authorize_shared(|| Arc::new(backend.clone()), token_url)
+            .await
+    }
+
+    /// rather than being tied to the caller, the request for a new token is stored alongside the
+    /// token, and everyone who needs a token waits on that same request. so if a caller is
+    /// cancelled (e.g. by a timeout) while the token is being fetched, the next caller picks the
+    /// request up where it left off, rather than it being abandoned and a duplicate one sent.
+    /// `backend` is only called if a new request needs starting.
+    pub(crate) async fn authorize_shared<F>(
+        &self,
+        backend: F,
+        token_url: &str,
+    ) -> Result<BearerToken, Error>
+    where
+        F: FnOnce() -> Arc<dyn HttpBackend>,
+    {
+        let refresh = {
+            let mut state = self.token.state();
+            if let Some(token) = state.token.as_ref().filter(|token| !token.is_expired()) {
+                return Ok(token.token.clone());
             }
-            Some(token) => {
-                if token.is_expired() {
-                    let new_token = self.definitely_authorize(http_client).await?;
-                    let ret = new_token.token.clone();
-                    *guard = Some(new_token);
-                    Ok(ret)
-                } else {
-                    Ok(token.token.clone())
+            state
+                .in_flight
+                .get_or_insert_with(|| self.start_refresh(backend(), token_url.into()))
+                .clone()
+        };
+        refresh
+            .await
+            .map(|token| token.token)
+            .map_err(Error::unshare)
+    }
+
+    /// a token request which stores the token once it gets it, to be put in `in_flight`
+    fn start_refresh(&self, backend: Arc<dyn HttpBackend>, token_url: String) -> SharedRefresh {
+        // only a weak reference, so the request doesn't keep the cache alive (it's holding onto
+        // the request, after all)
+        let cache = Arc::downgrade(&self.token);
+        let consumer_key = self.consumer_key.clone();
+        let consumer_secret = self.consumer_secret.clone();
+        let refresh: BoxFuture<'static, _> = Box::pin(async move {
+            let result = Self::refresh(&consumer_key, &consumer_secret, &*backend, &token_url)
+                .await
+                .map_err(Arc::new);
+            if let Some(cache) = std::sync::Weak::upgrade(&cache) {
+                let mut state = cache.state();
+                if let Ok(token) = &result {
+                    state.token = Some(token.clone());
                 }
+                state.in_flight = None;
             }
+            result
+        });
+        futures_util::FutureExt::shared(refresh)
+    }
+
+    /// forget the stored token if it's `token`, so the next call to
+    /// [`authorize`][Credentials::authorize] gets a new one. for when the api rejects a token
+    /// before it was due to expire, e.g. because it was revoked.
+    ///
+    /// (if a different token is stored, e.g. because another task already replaced the rejected
+    /// one, it's kept.)
+    pub fn invalidate(&self, token: &BearerToken) {
+        let mut state = self.token.state();
+        if state
+            .token
+            .as_ref()
+            .is_some_and(|stored| stored.token.0 == token.0)
+        {
+            state.token = None;
         }
     }
 }
@@ -246,4 +428,86 @@ mod tests {
         let token_str: String = token.into();
         assert_eq!(token_str, "hello world".to_string());
     }
+
+    #[test]
+    fn test_expiry_margin() {
+        let token = |expires_in| TokenWithExpiry {
+            token: BearerToken("token".into()),
+            expires_at: Instant::now() + expires_in,
+        };
+        assert!(token(EXPIRY_MARGIN / 2).is_expired());
+        assert!(!token(EXPIRY_MARGIN * 2).is_expired());
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_fingerprint() {
+        let fingerprint = Credentials::new("key", "secret").fingerprint();
+        // it has to stay the same between versions, since it's used in shared caches' keys
+        assert_eq!(fingerprint, "3dc94a19365b10ec");
+        assert_eq!(fingerprint, Credentials::new("key", "other").fingerprint());
+        assert_ne!(
+            fingerprint,
+            Credentials::new("other", "secret").fingerprint()
+        );
+        assert!(!fingerprint.contains("key"));
+    }
+
+    /// a backend that answers token requests once it's allowed to, counting how many it gets
+    #[cfg(feature = "client")]
+    struct GatedBackend {
+        requests: std::sync::atomic::AtomicUsize,
+        gate: tokio::sync::Semaphore,
+    }
+
+    #[cfg(feature = "client")]
+    impl HttpBackend for GatedBackend {
+        fn send(
+            &self,
+            _request: http::Request<bytes::Bytes>,
+        ) -> BoxFuture<'_, Result<http::Response<bytes::Bytes>, TransportError>> {
+            self.requests
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async {
+                self.gate.acquire().await.unwrap().forget();
+                Ok(http::Response::new(
+                    r#"{"access_token": "token", "expires_in": 3600}"#.into(),
+                ))
+            })
+        }
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_cancelled_refresh() {
+        let backend = Arc::new(GatedBackend {
+            requests: 0.into(),
+            gate: tokio::sync::Semaphore::new(0),
+        });
+        let requests = || backend.requests.load(std::sync::atomic::Ordering::SeqCst);
+
+        // a refresh that's given up on partway through is picked up by the next caller, rather
+        // than being sent again
+        let credentials = Credentials::new("key", "secret");
+        let authorize = credentials.authorize(&backend);
+        assert!(tokio::time::timeout(Duration::from_millis(10), authorize)
+            .await
+            .is_err());
+        backend.gate.add_permits(1);
+        let token = credentials.authorize(&backend).await.unwrap();
+        assert_eq!(token.to_string(), "token");
+        assert_eq!(requests(), 1);
+
+        // and it's the same refresh whichever way the token's asked for
+        let credentials = Credentials::new("key", "secret");
+        let dyn_backend: Arc<dyn HttpBackend> = backend.clone();
+        let (first, second, ()) = tokio::join!(
+            credentials.authorize(&backend),
+            credentials.authorize_shared(|| dyn_backend, DEFAULT_TOKEN_URL),
+            async { backend.gate.add_permits(1) },
+        );
+        assert_eq!(first.unwrap().to_string(), "token");
+        assert_eq!(second.unwrap().to_string(), "token");
+        assert_eq!(requests(), 2);
+    }
 }
@@ -0,0 +1,209 @@
+//! the transport used to actually send http requests.
+//!
+//! everything that talks to the network ([`Client`][crate::client::Client] and
+//! [`Credentials::authorize`][crate::auth::Credentials::authorize]) does so through an
+//! [`HttpBackend`], so you can swap out the http library being used (or skip the network entirely
+//! in tests). with the `reqwest` feature (on by default), [`reqwest::Client`] implements it.
+//!
+//! request bodies are usually [`Bytes`], so they can be cloned cheaply to retry a request. ones
+//! built a chunk at a time as they're sent (see
+//! [`add_attachment_stream`][crate::client::CreatePostRequestBuilder::add_attachment_stream])
+//! go through [`HttpBackend::send_streaming`] instead.
+//!
+//! # Examples
+//! a backend that always responds with the same thing
+//! ```
+//! use tumblr_api::backend::{bytes, http, BoxFuture, HttpBackend, TransportError};
+//!
+//! struct Canned(&'static str);
+//!
+//! impl HttpBackend for Canned {
+//!     fn send(
+//!         &self,
+//!         _request: http::Request<bytes::Bytes>,
+//!     ) -> BoxFuture<'_, Result<http::Response<bytes::Bytes>, TransportError>> {
+//!         let body = bytes::Bytes::from_static(self.0.as_bytes());
+//!         Box::pin(async move { Ok(http::Response::new(body)) })
+//!     }
+//! }
+//! ```
+
+use std::{error::Error as StdError, future::Future, pin::Pin, sync::Arc};
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{Stream, StreamExt};
+
+// re-exported so implementations can be sure they're using the same versions
+pub use bytes;
+pub use http;
+
+/// a boxed future, as returned by [`HttpBackend::send`]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// a request body that's produced a chunk at a time as it's sent, see
+/// [`HttpBackend::send_streaming`]
+pub type BodyStream =
+    Pin<Box<dyn Stream<Item = Result<Bytes, Box<dyn StdError + Send + Sync>>> + Send + Sync>>;
+
+/// something that can send an http request and return the response.
+///
+/// implementations should only fail with a [`TransportError`] when no response was received at
+/// all - error statuses are returned as normal responses.
+pub trait HttpBackend: Send + Sync {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>>;
+
+    /// send a request whose body is streamed rather than held in memory all at once.
+    ///
+    /// the default implementation reads the whole body into memory and then sends it with
+    /// [`send`][HttpBackend::send], so backends that can actually stream a body should override
+    /// it. an error from the body stream fails the request with a [`TransportErrorKind::Other`].
+    fn send_streaming(
+        &self,
+        request: http::Request<BodyStream>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>> {
+        Box::pin(async move {
+            let (parts, mut stream) = request.into_parts();
+            let mut body = BytesMut::new();
+            while let Some(chunk) = stream.next().await {
+                body.extend_from_slice(
+                    &chunk.map_err(|err| TransportError::new(TransportErrorKind::Other, err))?,
+                );
+            }
+            self.send(http::Request::from_parts(parts, body.freeze()))
+                .await
+        })
+    }
+}
+
+impl<T: HttpBackend + ?Sized> HttpBackend for Arc<T> {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>> {
+        (**self).send(request)
+    }
+
+    fn send_streaming(
+        &self,
+        request: http::Request<BodyStream>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>> {
+        (**self).send_streaming(request)
+    }
+}
+
+impl<T: HttpBackend + ?Sized> HttpBackend for Box<T> {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>> {
+        (**self).send(request)
+    }
+
+    fn send_streaming(
+        &self,
+        request: http::Request<BodyStream>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>> {
+        (**self).send_streaming(request)
+    }
+}
+
+/// what kind of failure a [`TransportError`] was, as far as deciding whether to retry goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// couldn't connect to the server
+    Connect,
+    /// the request timed out
+    Timeout,
+    Other,
+}
+
+/// a failure to send a request or receive its response
+#[derive(Debug, thiserror::Error)]
+#[error("{source}")]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    #[source]
+    source: Box<dyn StdError + Send + Sync>,
+}
+
+impl TransportError {
+    pub fn new<E>(kind: TransportErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        Self {
+            kind,
+            source: source.into(),
+        }
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+
+    /// the underlying error from the backend, e.g. a [`reqwest::Error`]
+    #[must_use]
+    pub fn get_ref(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        &*self.source
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for TransportError {
+    fn from(err: reqwest::Error) -> Self {
+        let kind = if err.is_connect() {
+            TransportErrorKind::Connect
+        } else if err.is_timeout() {
+            TransportErrorKind::Timeout
+        } else {
+            TransportErrorKind::Other
+        };
+        Self::new(kind, err)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpBackend for reqwest::Client {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>> {
+        Box::pin(async move { execute_reqwest(self, reqwest::Request::try_from(request)?).await })
+    }
+
+    fn send_streaming(
+        &self,
+        request: http::Request<BodyStream>,
+    ) -> BoxFuture<'_, Result<http::Response<Bytes>, TransportError>> {
+        let (parts, stream) = request.into_parts();
+        let request = http::Request::from_parts(parts, reqwest::Body::wrap_stream(stream));
+        Box::pin(async move { execute_reqwest(self, reqwest::Request::try_from(request)?).await })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+async fn execute_reqwest(
+    client: &reqwest::Client,
+    request: reqwest::Request,
+) -> Result<http::Response<Bytes>, TransportError> {
+    let response = client.execute(request).await?;
+    let mut builder = http::Response::builder()
+        .status(response.status())
+        .version(response.version());
+    if let Some(headers) = builder.headers_mut() {
+        headers.extend(
+            response
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+    }
+    let body = response.bytes().await?;
+    builder
+        .body(body)
+        .map_err(|err| TransportError::new(TransportErrorKind::Other, err))
+}
@@ -22,6 +22,29 @@
 //! # }
 //! ```
 //!
+//! creating a client that talks to somewhere other than `api.tumblr.com`
+//! ```no_run
+//! use tumblr_api::{client::Client, auth::Credentials};
+//! let client = Client::builder(Credentials::new("your consumer key", "your consumer secret"))
+//!     .api_base_url("http://localhost:8080/v2")
+//!     .oauth_token_url("http://localhost:8080/v2/oauth2/token")
+//!     .build();
+//! ```
+//!
+//! getting details about the response, not just its content
+//! ```no_run
+//! # use tumblr_api::{client::Client, auth::Credentials};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), tumblr_api::client::RequestError> {
+//! # let client = Client::new(Credentials::new("your consumer key", "your consumer secret"));
+//! let response = client.user_info().send_with_meta().await?;
+//! println!("{} {}", response.status, response.msg);
+//! println!("{:?}", response.headers.get("x-request-id"));
+//! let user_info = response.payload;
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! creating a post
 //! ```no_run
 //! # use tumblr_api::{client::Client, auth::Credentials};
@@ -39,19 +62,66 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! request builders can also be awaited directly, which is the same as calling `send`
+//! ```no_run
+//! # use tumblr_api::{client::Client, auth::Credentials};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), tumblr_api::client::RequestError> {
+//! # let client = Client::new(Credentials::new("your consumer key", "your consumer secret"));
+//! let user_info = client.user_info().await?;
+//! # Ok(())
+//! # }
+//! ```
 
 use tumblr_api_derive::Builder;
 
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
 use std::borrow::Cow;
-use std::{fmt::Debug, sync::Arc};
-
-use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use crate::{
-    api::{Response, SuccessResponse},
-    auth::{Credentials, Error as AuthError},
+    api::{
+        BlogLikesEndpoint, BlogPostsEndpoint, DashboardEndpoint, DraftsEndpoint, Endpoint,
+        EndpointError, FollowersEndpoint, NotesEndpoint, Paginated,
+    },
+    auth::{BearerToken, Credentials, Error as AuthError, DEFAULT_TOKEN_URL},
+    backend::{BodyStream, HttpBackend, TransportError, TransportErrorKind},
 };
 
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod cassette;
+mod governor;
+mod middleware;
+mod pagination;
+#[cfg(feature = "profiles")]
+pub mod profiles;
+mod rate_limit;
+mod retry;
+mod upload;
+
+use batch::Batch;
+use cache::ResponseCache;
+use cassette::{Cassette, CassetteError};
+pub use governor::{OnQuotaExhausted, Quota, QuotaGovernor};
+pub use middleware::{Middleware, ResponseInfo};
+pub use pagination::Paginator;
+pub use rate_limit::{RateLimitInfo, RateLimitWindow};
+pub use retry::RetryPolicy;
+use upload::{StreamedAttachment, StreamingBody};
+
+/// the base url api requests are sent to, unless overridden via [`ClientBuilder::api_base_url`]
+pub const DEFAULT_API_BASE_URL: &str = "https://api.tumblr.com/v2";
+
 #[derive(Clone)]
 pub struct Client {
     inner: Arc<ClientInner>,
@@ -59,7 +129,213 @@ pub struct Client {
 
 struct ClientInner {
     credentials: Credentials,
-    http_client: reqwest::Client,
+    backend: Arc<dyn HttpBackend>,
+    api_base_url: Box<str>,
+    oauth_token_url: Box<str>,
+    cassette: Option<CassetteMode>,
+    retry_policy: RetryPolicy,
+    /// from the most recent response that had rate limit headers
+    rate_limit: Mutex<Option<RateLimitInfo>>,
+    governor: Option<governor::GovernorState>,
+    keep_unparsable_bodies: bool,
+    middleware: Vec<Arc<dyn Middleware>>,
+    cache: Option<ResponseCache>,
+    timeout: Option<Duration>,
+}
+
+enum CassetteMode {
+    Record(cassette::Recorder),
+    Replay(cassette::Player),
+}
+
+/// an http response, before any parsing has been done
+type RawResponse = http::Response<bytes::Bytes>;
+
+/// a response, along with the result of parsing it
+type ParsedResponse<T> = (
+    RawResponse,
+    Result<crate::api::SuccessResponse<T>, EndpointError>,
+);
+
+/// a successful response, along with details about it beyond the response data itself.
+///
+/// returned by the `send_with_meta` method of request builders.
+#[derive(Debug)]
+pub struct Response<T> {
+    /// the response data, i.e. what the request builder's `send` would return
+    pub payload: T,
+    /// the http status of the response (e.g. `201 Created` vs `200 OK`)
+    pub status: http::StatusCode,
+    /// the status message the api included in the response body, see [`ResponseMeta::msg`][crate::api::ResponseMeta::msg]
+    pub msg: String,
+    /// the response's http headers, e.g. for ones this crate doesn't look at itself
+    pub headers: http::HeaderMap,
+    /// rate limit info reported with the response, if there was any
+    pub rate_limit: Option<RateLimitInfo>,
+}
+
+/// builder for a [`Client`] with non-default settings.
+///
+/// created via [`Client::builder`].
+pub struct ClientBuilder {
+    credentials: Credentials,
+    backend: Arc<dyn HttpBackend>,
+    api_base_url: Box<str>,
+    oauth_token_url: Box<str>,
+    cassette: Option<CassetteMode>,
+    retry_policy: RetryPolicy,
+    quota_governor: Option<QuotaGovernor>,
+    keep_unparsable_bodies: bool,
+    middleware: Vec<Arc<dyn Middleware>>,
+    cache: Option<ResponseCache>,
+    timeout: Option<Duration>,
+}
+
+impl ClientBuilder {
+    /// send requests (both to the api and for access tokens) through `backend`, see the
+    /// [`backend`][crate::backend] module.
+    #[must_use]
+    pub fn backend<B: HttpBackend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+
+    /// use an existing [`reqwest::Client`] for sending requests, e.g. to configure proxies, tls,
+    /// timeouts, the user agent, or connection pooling. it's used both for api requests and for
+    /// requesting access tokens. defaults to a [`reqwest::Client`] that doesn't follow redirects.
+    ///
+    /// [`Client::avatar`] only works if redirects aren't followed (which reqwest does by
+    /// default), since the api's response to it is a redirect to the image.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tumblr_api::{auth::Credentials, client::Client};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let http_client = reqwest::Client::builder()
+    ///     .user_agent("my-app/1.0")
+    ///     .timeout(Duration::from_secs(10))
+    ///     .redirect(reqwest::redirect::Policy::none())
+    ///     .build()?;
+    /// let client = Client::builder(Credentials::new("your consumer key", "your consumer secret"))
+    ///     .http_client(http_client)
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "reqwest")]
+    #[must_use]
+    pub fn http_client(self, http_client: reqwest::Client) -> Self {
+        self.backend(http_client)
+    }
+
+    /// set the url that api paths (e.g. `user/info`) are appended to.
+    /// defaults to [`DEFAULT_API_BASE_URL`].
+    #[must_use]
+    pub fn api_base_url<S: Into<Box<str>>>(mut self, api_base_url: S) -> Self {
+        self.api_base_url = api_base_url.into();
+        self
+    }
+
+    /// set the url access tokens are requested from.
+    /// defaults to [`DEFAULT_TOKEN_URL`].
+    #[must_use]
+    pub fn oauth_token_url<S: Into<Box<str>>>(mut self, oauth_token_url: S) -> Self {
+        self.oauth_token_url = oauth_token_url.into();
+        self
+    }
+
+    /// record every request the client sends (and the response it gets back) to a
+    /// [`Cassette`] at `path`, see the [`cassette`] module. the file is written when the client
+    /// is dropped, or when [`Client::save_cassette`] is called.
+    ///
+    /// overrides any earlier call to [`ClientBuilder::replay_cassette`].
+    #[must_use]
+    pub fn record_cassette<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.cassette = Some(CassetteMode::Record(cassette::Recorder::new(path.into())));
+        self
+    }
+
+    /// serve responses from `cassette` instead of sending requests over the network, see the
+    /// [`cassette`] module.
+    ///
+    /// overrides any earlier call to [`ClientBuilder::record_cassette`].
+    #[must_use]
+    pub fn replay_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(CassetteMode::Replay(cassette::Player::new(cassette)));
+        self
+    }
+
+    /// set how failed requests are retried. defaults to [`RetryPolicy::none`].
+    #[must_use]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// track the api's daily quotas client-side, see [`QuotaGovernor`]. off by default.
+    #[must_use]
+    pub fn quota_governor(mut self, quota_governor: QuotaGovernor) -> Self {
+        self.quota_governor = Some(quota_governor);
+        self
+    }
+
+    /// include the full body of responses that can't be parsed in the
+    /// [`RequestError::Deserializing`] they cause. off by default, since bodies can be large.
+    #[must_use]
+    pub const fn keep_unparsable_bodies(mut self, keep_unparsable_bodies: bool) -> Self {
+        self.keep_unparsable_bodies = keep_unparsable_bodies;
+        self
+    }
+
+    /// add a [`Middleware`], which will see every api request the client makes (and can modify
+    /// it, or stop it from being sent) along with the response. middlewares are called in the
+    /// order they were added.
+    #[must_use]
+    pub fn middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// cache responses to requests that are made repeatedly (like fetching a blog's info), see
+    /// the [`cache`] module. off by default.
+    #[must_use]
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// give up on requests that take longer than `timeout`, failing them with a
+    /// [`TransportErrorKind::Timeout`] error (which the [retry policy][Self::retry_policy] can
+    /// retry). individual requests can override this via their builder's `timeout` method, e.g.
+    /// to give large uploads longer. no timeout by default.
+    ///
+    /// the timeout applies to each attempt separately, and doesn't include getting an access
+    /// token.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Client {
+        Client {
+            inner: Arc::new(ClientInner {
+                credentials: self.credentials,
+                backend: self.backend,
+                api_base_url: self.api_base_url,
+                oauth_token_url: self.oauth_token_url,
+                cassette: self.cassette,
+                retry_policy: self.retry_policy,
+                rate_limit: Mutex::default(),
+                governor: self.quota_governor.map(governor::GovernorState::new),
+                keep_unparsable_bodies: self.keep_unparsable_bodies,
+                middleware: self.middleware,
+                cache: self.cache,
+                timeout: self.timeout,
+            }),
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -67,64 +343,686 @@ pub enum RequestError {
     #[error(transparent)]
     Auth(#[from] AuthError),
     #[error(transparent)]
-    Network(#[from] reqwest::Error),
-    #[error(transparent)]
-    Deserializing(#[from] serde_json::Error),
+    Network(#[from] TransportError),
+    /// the response was json, but didn't have the expected shape
+    #[error("{source}")]
+    Deserializing {
+        #[source]
+        source: crate::api::ResponseParseError,
+        /// the whole response body, if the client was set to keep it (see
+        /// [`ClientBuilder::keep_unparsable_bodies`])
+        body: Option<String>,
+    },
+    /// the api responded with something that isn't a json api response at all, e.g. an html
+    /// error page from a proxy, or an empty body
+    #[error("unexpected {status} response{}: {body_snippet:?}", content_type.as_deref().map(|content_type| format!(" ({content_type})")).unwrap_or_default())]
+    UnexpectedResponse {
+        status: http::StatusCode,
+        content_type: Option<String>,
+        /// the start of the response body
+        body_snippet: String,
+    },
+    #[error("couldn't serialize request body: {0}")]
+    Serializing(#[source] serde_json::Error),
+    #[error("invalid mime type {mime_type:?}")]
+    InvalidMimeType { mime_type: String },
+    /// the request couldn't be built, e.g. because the blog name made for an invalid url
+    #[error("invalid request: {0}")]
+    InvalidRequest(#[source] http::Error),
+    #[error("couldn't format date: {0}")]
+    FormattingDate(#[from] time::error::Format),
     #[error(transparent)]
     Api(#[from] crate::api::ResponseError),
+    #[error(transparent)]
+    Cassette(#[from] CassetteError),
+    /// a [`Middleware`] stopped the request from being sent
+    #[error("request stopped by middleware: {0}")]
+    Middleware(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("{quota:?} quota exhausted until {reset_at}")]
+    QuotaExhausted {
+        quota: Quota,
+        reset_at: time::OffsetDateTime,
+    },
+}
+
+impl From<EndpointError> for RequestError {
+    fn from(err: EndpointError) -> Self {
+        match err {
+            EndpointError::Serializing(err) => Self::Serializing(err),
+            EndpointError::InvalidMimeType { mime_type } => Self::InvalidMimeType { mime_type },
+            EndpointError::InvalidRequest(err) => Self::InvalidRequest(err),
+            EndpointError::UnexpectedResponse {
+                status,
+                content_type,
+                body_snippet,
+            } => Self::UnexpectedResponse {
+                status,
+                content_type,
+                body_snippet,
+            },
+            EndpointError::Deserializing(source) => Self::Deserializing { source, body: None },
+            EndpointError::Api(err) => Self::Api(err),
+        }
+    }
+}
+
+impl RequestError {
+    /// the error the api responded with, if that's what this is
+    fn api_error(&self) -> Option<&crate::api::ResponseError> {
+        match self {
+            Self::Api(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    fn api_status(&self) -> Option<i32> {
+        match self {
+            Self::Api(err) => Some(err.meta.status),
+            Self::UnexpectedResponse { status, .. } => Some(status.as_u16().into()),
+            _ => None,
+        }
+    }
+
+    /// whether the api rejected the request for exceeding its rate limits (`429 Too Many Requests`)
+    #[must_use]
+    pub fn is_rate_limited(&self) -> bool {
+        self.api_status() == Some(429)
+    }
+
+    /// whether the api responded with `404 Not Found`, e.g. because a blog or post doesn't exist
+    /// (see also [`ApiErrorCode::BlogNotFound`][crate::api::ApiErrorCode::BlogNotFound])
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        self.api_status() == Some(404)
+            || self
+                .api_error()
+                .is_some_and(|err| err.has_error_code(crate::api::ApiErrorCode::BlogNotFound))
+    }
+
+    /// whether this is a problem with authentication - either getting a token failed, or the api
+    /// rejected it (`401`/`403`, or an [`ApiErrorCode::Auth`][crate::api::ApiErrorCode::Auth]
+    /// subcode)
+    #[must_use]
+    pub fn is_auth(&self) -> bool {
+        match self {
+            Self::Auth(_) => true,
+            Self::Api(err) => {
+                matches!(err.meta.status, 401 | 403)
+                    || err.errors.iter().any(|entry| {
+                        matches!(entry.error_code(), crate::api::ApiErrorCode::Auth(_))
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// whether the api rejected a post's content as invalid NPF
+    #[must_use]
+    pub fn is_invalid_npf(&self) -> bool {
+        self.api_error()
+            .is_some_and(|err| err.has_error_code(crate::api::ApiErrorCode::InvalidNpf))
+    }
+
+    /// whether the api responded with a `5xx` status
+    #[must_use]
+    pub fn is_server_error(&self) -> bool {
+        self.api_status()
+            .is_some_and(|status| (500..600).contains(&status))
+    }
+
+    /// whether the api rejected a post for exceeding the daily post limit, or the request was
+    /// held back because it would have (see [`QuotaGovernor`])
+    #[must_use]
+    pub fn is_post_limit_exceeded(&self) -> bool {
+        matches!(
+            self,
+            Self::QuotaExhausted {
+                quota: Quota::Posts,
+                ..
+            }
+        ) || self
+            .api_error()
+            .is_some_and(|err| err.has_error_code(crate::api::ApiErrorCode::PostLimitExceeded))
+    }
 }
 
 impl ClientInner {
-    async fn do_request<RT, U, B>(
+    /// send a request to `endpoint`, and parse the response
+    async fn request<E: Endpoint>(
         &self,
-        method: reqwest::Method,
-        url: U,
-        json: Option<B>,
-        parts: Option<Vec<(Cow<'static, str>, reqwest::multipart::Part)>>,
-    ) -> Result<SuccessResponse<RT>, RequestError>
-    where
-        RT: DeserializeOwned,
-        U: reqwest::IntoUrl,
-        B: Serialize + Sized,
-    {
-        let mut request_builder = self.http_client.request(method, url);
-        let token = self.credentials.authorize(&self.http_client).await?;
-        request_builder = request_builder.bearer_auth(token);
-        if let Some(parts) = parts {
-            let mut form = reqwest::multipart::Form::new();
-            if let Some(json) = json {
-                let body_part =
-                    reqwest::multipart::Part::text(serde_json::to_string(&json).unwrap()) // TODO handle instead of unwrapping
-                        .mime_str("application/json")
-                        .unwrap(); // TODO handle instead of unwrapping?
-                form = form.part("json", body_part);
-                for (part_id, part) in parts {
-                    form = form.part(part_id, part);
+        endpoint: &E,
+    ) -> Result<Response<E::Response>, RequestError> {
+        self.request_with_timeout(endpoint, None).await
+    }
+
+    /// [`ClientInner::request`], with `timeout` overriding the client's default timeout
+    async fn request_with_timeout<E: Endpoint>(
+        &self,
+        endpoint: &E,
+        timeout: Option<Duration>,
+    ) -> Result<Response<E::Response>, RequestError> {
+        self.request_with_body(endpoint, None, timeout).await
+    }
+
+    /// [`ClientInner::request_with_timeout`], sending `streamed` as the body instead of the one
+    /// `endpoint` builds if given
+    async fn request_with_body<E: Endpoint>(
+        &self,
+        endpoint: &E,
+        streamed: Option<StreamingBody>,
+        timeout: Option<Duration>,
+    ) -> Result<Response<E::Response>, RequestError> {
+        let future = self.request_uninstrumented(endpoint, streamed, timeout.or(self.timeout));
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(
+            future,
+            tracing::info_span!(
+                "tumblr_api.request",
+                method = %endpoint.method(),
+                endpoint = E::PATH_TEMPLATE,
+                blog = endpoint.blog_identifier(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                retries = tracing::field::Empty,
+            ),
+        );
+        let result = future.await;
+        #[cfg(feature = "tracing")]
+        if let Err(err) = &result {
+            tracing::debug!(error = %err, "request failed");
+        }
+        result
+    }
+
+    async fn request_uninstrumented<E: Endpoint>(
+        &self,
+        endpoint: &E,
+        streamed: Option<StreamingBody>,
+        timeout: Option<Duration>,
+    ) -> Result<Response<E::Response>, RequestError> {
+        // middleware sees the request once, without its authorization, however many times it
+        // ends up being sent. (a streamed body can't be looked at without using it up, so
+        // middleware sees those as empty.)
+        let mut request = endpoint.to_request(&self.api_base_url, None)?;
+        let streamed = match streamed {
+            Some(body) => Some(
+                body.replace_body(&mut request)
+                    .map_err(RequestError::InvalidRequest)?,
+            ),
+            None => None,
+        };
+        for middleware in &self.middleware {
+            middleware
+                .on_request(E::PATH_TEMPLATE, &mut request)
+                .map_err(RequestError::Middleware)?;
+        }
+        let method = request.method().clone();
+
+        let sent_at = Instant::now();
+        let result = match streamed {
+            None => self.send_authorized(endpoint, &request, timeout).await,
+            Some(stream) => self.send_streamed::<E>(&request, stream, timeout).await,
+        };
+        let (raw, parsed) = match result {
+            Ok(result) => result,
+            Err(RequestError::Network(err)) => {
+                for middleware in &self.middleware {
+                    middleware.on_transport_error(E::PATH_TEMPLATE, &err);
+                }
+                return Err(RequestError::Network(err));
+            }
+            Err(err) => return Err(err),
+        };
+        let elapsed = sent_at.elapsed();
+        #[cfg(feature = "tracing")]
+        tracing::Span::current()
+            .record("status", raw.status().as_u16())
+            .record(
+                "latency_ms",
+                u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+            );
+        let rate_limit = RateLimitInfo::from_headers(raw.headers());
+        if rate_limit.is_some() {
+            *self
+                .rate_limit
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = rate_limit;
+        }
+        if !self.middleware.is_empty() {
+            let info = ResponseInfo {
+                endpoint: E::PATH_TEMPLATE,
+                method: &method,
+                status: raw.status(),
+                headers: raw.headers(),
+                meta: match &parsed {
+                    Ok(resp) => Some(&resp.meta),
+                    Err(EndpointError::Api(err)) => Some(&err.meta),
+                    Err(_) => None,
+                },
+                elapsed,
+            };
+            for middleware in &self.middleware {
+                middleware.on_response(&info);
+            }
+        }
+        let resp = parsed.map_err(|err| match err {
+            EndpointError::Deserializing(source) => RequestError::Deserializing {
+                source,
+                body: self
+                    .keep_unparsable_bodies
+                    .then(|| String::from_utf8_lossy(raw.body()).into_owned()),
+            },
+            err => err.into(),
+        })?;
+        let (parts, _) = raw.into_parts();
+        Ok(Response {
+            payload: resp.response,
+            status: parts.status,
+            msg: resp.meta.msg,
+            headers: parts.headers,
+            rate_limit,
+        })
+    }
+
+    /// send `request` to `endpoint` with an access token, getting a new token and sending it
+    /// again if the api rejects the first one. returns the response, along with the result of
+    /// parsing it. a fresh cached response is returned without getting a token at all.
+    async fn send_authorized<E: Endpoint>(
+        &self,
+        endpoint: &E,
+        request: &http::Request<Bytes>,
+        timeout: Option<Duration>,
+    ) -> Result<ParsedResponse<E::Response>, RequestError> {
+        let cache_entry = self.cache_entry(endpoint.cacheable(), request);
+        let mut stale = None;
+        if let Some((cache, key)) = &cache_entry {
+            match cache.lookup(key).await {
+                cache::Lookup::Fresh(raw) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("using cached response");
+                    let parsed = E::parse_response(&raw);
+                    return Ok((raw, parsed));
+                }
+                cache::Lookup::Stale(cached) => stale = Some(cached),
+                cache::Lookup::Miss => {}
+            }
+        }
+        let mut reauthorized = false;
+        loop {
+            let (mut parts, body) = clone_request(request).into_parts();
+            let token = self.authorize(&mut parts).await?;
+            let attempt = http::Request::from_parts(parts, body);
+            let raw = self
+                .execute_cached(cache_entry.as_ref(), stale.clone(), attempt, timeout)
+                .await?;
+            let parsed = E::parse_response(&raw);
+
+            // if the token was revoked before it was due to expire, get a new one and try again
+            // (just once, in case something else is wrong)
+            if let Some(token) = &token {
+                if !reauthorized && token_rejected(&raw, &parsed) {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("token rejected, reauthorizing");
+                    self.credentials.invalidate(token);
+                    reauthorized = true;
+                    continue;
+                }
+            }
+            return Ok((raw, parsed));
+        }
+    }
+
+    /// send `request` to `endpoint` with `stream` as its body and an access token. since the
+    /// body can only be read once, the request is never retried - if the api rejects the token,
+    /// it's forgotten so the next request gets a new one, but this one still fails.
+    async fn send_streamed<E: Endpoint>(
+        &self,
+        request: &http::Request<Bytes>,
+        stream: BodyStream,
+        timeout: Option<Duration>,
+    ) -> Result<ParsedResponse<E::Response>, RequestError> {
+        let (mut parts, _) = clone_request(request).into_parts();
+        let token = self.authorize(&mut parts).await?;
+        let raw = self
+            .execute_streaming(http::Request::from_parts(parts, stream), timeout)
+            .await?;
+        let parsed = E::parse_response(&raw);
+        if let Some(token) = &token {
+            if token_rejected(&raw, &parsed) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("token rejected, not retrying streamed request");
+                self.credentials.invalidate(token);
+            }
+        }
+        Ok((raw, parsed))
+    }
+
+    /// add an access token to a request, returning it. replayed requests never reach the
+    /// network, so don't need (or get) a real token.
+    async fn authorize(
+        &self,
+        parts: &mut http::request::Parts,
+    ) -> Result<Option<BearerToken>, RequestError> {
+        if matches!(self.cassette, Some(CassetteMode::Replay(_))) {
+            return Ok(None);
+        }
+        let token = self
+            .credentials
+            .authorize_shared(|| Arc::clone(&self.backend), &self.oauth_token_url)
+            .await?;
+        let mut value = http::HeaderValue::try_from(format!("Bearer {token}"))
+            .map_err(|err| RequestError::InvalidRequest(err.into()))?;
+        value.set_sensitive(true);
+        parts.headers.insert(http::header::AUTHORIZATION, value);
+        Ok(Some(token))
+    }
+
+    /// send a request to `endpoint` that uses up `usage`, going through the governor if the
+    /// client has one. `streamed` is sent as the body instead of the one `endpoint` builds, if
+    /// given.
+    async fn request_with_quota<E: Endpoint>(
+        &self,
+        endpoint: &E,
+        usage: &[(Quota, i64)],
+        streamed: Option<StreamingBody>,
+        timeout: Option<Duration>,
+    ) -> Result<Response<E::Response>, RequestError> {
+        let reserved = self.acquire_quota(usage).await?;
+        let response = self.request_with_body(endpoint, streamed, timeout).await;
+        if reserved && response.is_err() {
+            if let Some(governor) = &self.governor {
+                governor.refund(usage);
+            }
+        }
+        response
+    }
+
+    /// make sure sending a request that uses up `usage` won't exceed any quotas, waiting or
+    /// failing if it would (depending on the governor's settings), and set that usage aside.
+    /// returns whether anything was set aside, which won't be the case if the client doesn't
+    /// have a governor.
+    async fn acquire_quota(&self, usage: &[(Quota, i64)]) -> Result<bool, RequestError> {
+        let Some(governor) = &self.governor else {
+            return Ok(false);
+        };
+        let mut just_refreshed = false;
+        loop {
+            match governor.reserve(usage) {
+                governor::Check::Reserved => return Ok(true),
+                // if the limits are somehow stale right after being fetched, trust the api to
+                // tell us if we're over them instead of fetching forever
+                governor::Check::Stale if just_refreshed => return Ok(false),
+                governor::Check::Stale => {
+                    // (if another request fetched them while we waited, no need to again)
+                    if let Some(_refreshing) = governor.start_refresh(usage).await {
+                        let limits = self.request(&crate::api::UserLimitsEndpoint).await?;
+                        governor.update(limits.payload.user);
+                    }
+                    just_refreshed = true;
+                }
+                governor::Check::Exhausted { quota, reset_at } => match governor.on_exhausted() {
+                    OnQuotaExhausted::FailFast => {
+                        return Err(RequestError::QuotaExhausted { quota, reset_at })
+                    }
+                    OnQuotaExhausted::Wait => {
+                        let wait = reset_at - time::OffsetDateTime::now_utc();
+                        tokio::time::sleep(wait.try_into().unwrap_or_default()).await;
+                        governor.invalidate();
+                        just_refreshed = false;
+                    }
+                },
+            }
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimitInfo> {
+        *self
+            .rate_limit
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// describe the request that would be sent to `endpoint`, without sending it
+    fn prepare<E: Endpoint>(
+        &self,
+        endpoint: &E,
+    ) -> Result<crate::api::PreparedRequest, RequestError> {
+        Ok(endpoint.prepare(&self.api_base_url)?)
+    }
+
+    /// the response cache and the key to look `request` up under, if there is a cache and the
+    /// request is `cacheable`
+    fn cache_entry(
+        &self,
+        cacheable: bool,
+        request: &http::Request<Bytes>,
+    ) -> Option<(&ResponseCache, String)> {
+        let cache = self.cache.as_ref()?;
+        if !cacheable || request.method() != http::Method::GET {
+            return None;
+        }
+        Some((
+            cache,
+            format!("{} {}", self.credentials.fingerprint(), request.uri()),
+        ))
+    }
+
+    /// forget any cached response to `endpoint`, e.g. because a request just changed what it
+    /// would return
+    async fn invalidate_cached<E: Endpoint>(&self, endpoint: &E) {
+        let Ok(request) = endpoint.to_request(&self.api_base_url, None) else {
+            return;
+        };
+        if let Some((cache, key)) = self.cache_entry(endpoint.cacheable(), &request) {
+            cache.invalidate(&key).await;
+        }
+    }
+
+    /// [`ClientInner::execute_with_retries`], storing the response in `cache_entry` (see
+    /// [`ClientInner::cache_entry`]) if given, and revalidating `stale` if there's an older
+    /// response in there
+    async fn execute_cached(
+        &self,
+        cache_entry: Option<&(&ResponseCache, String)>,
+        stale: Option<cache::CachedResponse>,
+        mut request: http::Request<Bytes>,
+        timeout: Option<Duration>,
+    ) -> Result<RawResponse, RequestError> {
+        let Some((cache, key)) = cache_entry else {
+            return self.execute_with_retries(request, timeout).await;
+        };
+        if let Some(etag) = stale
+            .as_ref()
+            .and_then(|stale| stale.etag.as_deref())
+            .and_then(|etag| http::HeaderValue::from_str(etag).ok())
+        {
+            request
+                .headers_mut()
+                .insert(http::header::IF_NONE_MATCH, etag);
+        }
+        let response = self.execute_with_retries(request, timeout).await?;
+        Ok(cache.update(key, stale, response).await)
+    }
+
+    /// [`ClientInner::execute`], retrying according to the client's [`RetryPolicy`]
+    async fn execute_with_retries(
+        &self,
+        mut request: http::Request<Bytes>,
+        timeout: Option<Duration>,
+    ) -> Result<RawResponse, RequestError> {
+        let may_retry = self.retry_policy.allows_retrying(request.method());
+        let mut attempts = 1;
+        loop {
+            let next_request = if may_retry && self.retry_policy.allows_attempt_after(attempts) {
+                Some(clone_request(&request))
+            } else {
+                None
+            };
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("retries", attempts - 1);
+            let result = self.execute(request, timeout).await;
+            let Some(next_request) = next_request else {
+                return result;
+            };
+            let delay = match &result {
+                Ok(response) if retry::is_retryable_status(response.status()) => self
+                    .retry_policy
+                    .backoff(attempts, retry::retry_after(response.headers())),
+                Err(RequestError::Network(err)) if retry::is_retryable_error(err) => {
+                    self.retry_policy.backoff(attempts, None)
                 }
+                _ => return result,
+            };
+            #[cfg(feature = "tracing")]
+            tracing::debug!(attempt = attempts, ?delay, "retrying request");
+            // no point making replayed requests wait
+            if !matches!(self.cassette, Some(CassetteMode::Replay(_))) {
+                tokio::time::sleep(delay).await;
             }
-            request_builder = request_builder.multipart(form);
-        } else if let Some(json) = json {
-            request_builder = request_builder.json(&json);
+            request = next_request;
+            attempts += 1;
         }
+    }
 
-        let bytes = request_builder.send().await?.bytes().await?;
-        let resp: Response<RT> = serde_json::from_slice(&bytes)?;
-        let resp: crate::api::ResponseResult<RT> = resp.into();
-        resp.map_err(RequestError::from)
+    /// send a request, going through the cassette if there is one
+    async fn execute(
+        &self,
+        request: http::Request<Bytes>,
+        timeout: Option<Duration>,
+    ) -> Result<RawResponse, RequestError> {
+        match &self.cassette {
+            None => self.send(request, timeout).await,
+            Some(CassetteMode::Replay(player)) => Ok(player.replay(&request)?),
+            Some(CassetteMode::Record(recorder)) => {
+                let recorded_request = cassette::RecordedRequest::new(&request);
+                let response = self.send(request, timeout).await?;
+                recorder.record(recorded_request, &response);
+                Ok(response)
+            }
+        }
+    }
+
+    /// [`ClientInner::execute`], for a request with a streamed body. recording one leaves its
+    /// body out, and replaying one only goes by its method and url.
+    async fn execute_streaming(
+        &self,
+        request: http::Request<BodyStream>,
+        timeout: Option<Duration>,
+    ) -> Result<RawResponse, RequestError> {
+        match &self.cassette {
+            None => with_timeout(self.backend.send_streaming(request), timeout).await,
+            Some(CassetteMode::Replay(player)) => {
+                Ok(player.replay(&with_body(&request, Bytes::new()))?)
+            }
+            Some(CassetteMode::Record(recorder)) => {
+                let recorded_request =
+                    cassette::RecordedRequest::new(&with_body(&request, Bytes::new()));
+                let response = with_timeout(self.backend.send_streaming(request), timeout).await?;
+                recorder.record(recorded_request, &response);
+                Ok(response)
+            }
+        }
+    }
+
+    /// send a request through the backend, giving up after `timeout` if there is one
+    async fn send(
+        &self,
+        request: http::Request<Bytes>,
+        timeout: Option<Duration>,
+    ) -> Result<RawResponse, RequestError> {
+        with_timeout(self.backend.send(request), timeout).await
+    }
+}
+
+/// wait for `response`, giving up after `timeout` if there is one
+async fn with_timeout(
+    response: crate::backend::BoxFuture<'_, Result<RawResponse, TransportError>>,
+    timeout: Option<Duration>,
+) -> Result<RawResponse, RequestError> {
+    let Some(timeout) = timeout else {
+        return Ok(response.await?);
+    };
+    match tokio::time::timeout(timeout, response).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(TransportError::new(
+            TransportErrorKind::Timeout,
+            format!("request timed out after {timeout:?}"),
+        )
+        .into()),
     }
 }
 
+/// whether the api rejected the token a request was sent with
+fn token_rejected<T>(raw: &RawResponse, parsed: &Result<T, EndpointError>) -> bool {
+    raw.status() == http::StatusCode::UNAUTHORIZED
+        || matches!(parsed, Err(EndpointError::Api(err)) if err.errors.iter().any(|entry| {
+            matches!(entry.error_code(), crate::api::ApiErrorCode::Auth(_))
+        }))
+}
+
+/// `http::Request` isn't `Clone`, since bodies in general might not be
+fn clone_request(request: &http::Request<Bytes>) -> http::Request<Bytes> {
+    with_body(request, request.body().clone())
+}
+
+/// a copy of `request`, with `body` instead of its own
+fn with_body<T>(request: &http::Request<T>, body: Bytes) -> http::Request<Bytes> {
+    let mut clone = http::Request::new(body);
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    clone
+}
+
 impl Client {
+    #[cfg(feature = "reqwest")]
     #[must_use]
     pub fn new(credentials: Credentials) -> Self {
-        Self {
-            inner: Arc::new(ClientInner {
-                http_client: reqwest::Client::new(),
-                credentials,
-            }),
+        Self::builder(credentials).build()
+    }
+
+    /// create a [`ClientBuilder`], for configuring a client beyond just its credentials
+    #[cfg(feature = "reqwest")]
+    #[must_use]
+    pub fn builder(credentials: Credentials) -> ClientBuilder {
+        // the api answers `avatar` with a redirect, which has to be parsed rather than followed
+        let http_client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self::builder_with_backend(credentials, http_client)
+    }
+
+    /// create a [`ClientBuilder`] for a client that sends requests through `backend`, see the
+    /// [`backend`][crate::backend] module
+    #[must_use]
+    pub fn builder_with_backend<B: HttpBackend + 'static>(
+        credentials: Credentials,
+        backend: B,
+    ) -> ClientBuilder {
+        ClientBuilder {
+            credentials,
+            backend: Arc::new(backend),
+            api_base_url: DEFAULT_API_BASE_URL.into(),
+            oauth_token_url: DEFAULT_TOKEN_URL.into(),
+            cassette: None,
+            retry_policy: RetryPolicy::none(),
+            quota_governor: None,
+            keep_unparsable_bodies: false,
+            middleware: Vec::new(),
+            cache: None,
+            timeout: None,
         }
     }
 
+    /// the rate limits reported by the most recent response that included them, or `None` if
+    /// there hasn't been one yet
+    #[must_use]
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        self.inner.rate_limit()
+    }
+
     #[must_use]
     pub fn user_info(&self) -> UserInfoRequestBuilder {
         UserInfoRequestBuilder::new(self.clone())
@@ -143,27 +1041,466 @@ impl Client {
     pub fn api_limits(&self) -> ApiLimitsRequestBuilder {
         ApiLimitsRequestBuilder::new(self.clone())
     }
+
+    /// a blog's info, e.g. its title and description. this can be cached, see
+    /// [`ClientBuilder::cache`].
+    #[must_use]
+    pub fn blog_info<B: Into<Box<str>>>(&self, blog_identifier: B) -> BlogInfoRequestBuilder {
+        BlogInfoRequestBuilder::new(self.clone(), blog_identifier.into())
+    }
+
+    /// where a blog's avatar image is. this can be cached, see [`ClientBuilder::cache`].
+    #[must_use]
+    pub fn avatar<B: Into<Box<str>>>(&self, blog_identifier: B) -> AvatarRequestBuilder {
+        AvatarRequestBuilder::new(self.clone(), blog_identifier.into())
+    }
+
+    /// fetch a single post. this can be cached, see [`ClientBuilder::cache`].
+    #[must_use]
+    pub fn post<B: Into<Box<str>>>(&self, blog_identifier: B, post_id: i64) -> PostRequestBuilder {
+        PostRequestBuilder::new(self.clone(), blog_identifier.into(), post_id)
+    }
+
+    /// a request to any endpoint, including ones this crate doesn't support yet. goes through
+    /// all the same handling (authorization, retries, error parsing, etc.) as any other request.
+    ///
+    /// `path` is relative to the api base url, e.g. `blog/staff/followers`. a leading `/` or
+    /// `/v2/` is left off, so a link from the api (like a response's `_links.next.href`) can be
+    /// passed as-is, and any query string in it is sent along with the ones added via
+    /// [`query`][RawRequestBuilder::query].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use tumblr_api::{auth::Credentials, backend::http::Method, client::Client};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), tumblr_api::client::RequestError> {
+    /// # let client = Client::new(Credentials::new("your consumer key", "your consumer secret"));
+    /// let followers = client
+    ///     .raw(Method::GET, "blog/staff/followers")
+    ///     .query("limit", "5")
+    ///     .send()
+    ///     .await?;
+    /// println!("{}", followers["total_users"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn raw<P: Into<Box<str>>>(&self, method: http::Method, path: P) -> RawRequestBuilder {
+        RawRequestBuilder::new(self.clone(), method, path.into())
+    }
+
+    /// if the client is [recording a cassette][ClientBuilder::record_cassette], write what's
+    /// been recorded so far to it. this also happens when the client is dropped, but any error
+    /// is lost then.
+    ///
+    /// # Errors
+    /// returns an error if the cassette file can't be written
+    pub fn save_cassette(&self) -> Result<(), CassetteError> {
+        match &self.inner.cassette {
+            Some(CassetteMode::Record(recorder)) => recorder.save(),
+            _ => Ok(()),
+        }
+    }
+
+    /// start a [`Batch`] of operations on posts
+    pub fn batch(&self) -> Batch {
+        Batch::new(self.inner.clone())
+    }
+
+    /// stream the results of a list endpoint, see [`Paginator`]
+    pub fn paginate<E>(&self, endpoint: E) -> Paginator<E>
+    where
+        E: Paginated + Send + Sync + 'static,
+        E::Response: Send,
+        E::Item: Send + 'static,
+    {
+        Paginator::new(self.inner.clone(), endpoint)
+    }
+
+    /// a blog's published posts, newest first
+    pub fn blog_posts<B: Into<Box<str>>>(
+        &self,
+        blog_identifier: B,
+    ) -> Paginator<BlogPostsEndpoint> {
+        self.paginate(BlogPostsEndpoint {
+            blog_identifier: blog_identifier.into().into(),
+            ..Default::default()
+        })
+    }
+
+    /// a blog's draft posts, newest first
+    pub fn drafts<B: Into<Box<str>>>(&self, blog_identifier: B) -> Paginator<DraftsEndpoint> {
+        self.paginate(DraftsEndpoint {
+            blog_identifier: blog_identifier.into().into(),
+            ..Default::default()
+        })
+    }
+
+    /// the posts a blog has liked, most recently liked first
+    pub fn blog_likes<B: Into<Box<str>>>(
+        &self,
+        blog_identifier: B,
+    ) -> Paginator<BlogLikesEndpoint> {
+        self.paginate(BlogLikesEndpoint {
+            blog_identifier: blog_identifier.into().into(),
+            ..Default::default()
+        })
+    }
+
+    /// the users following a blog
+    pub fn followers<B: Into<Box<str>>>(&self, blog_identifier: B) -> Paginator<FollowersEndpoint> {
+        self.paginate(FollowersEndpoint {
+            blog_identifier: blog_identifier.into().into(),
+            ..Default::default()
+        })
+    }
+
+    /// the posts on the user's dashboard, newest first
+    pub fn dashboard(&self) -> Paginator<DashboardEndpoint> {
+        self.paginate(DashboardEndpoint::default())
+    }
+
+    /// the notes on a post, newest first
+    pub fn notes<B: Into<Box<str>>>(
+        &self,
+        blog_identifier: B,
+        post_id: i64,
+    ) -> Paginator<NotesEndpoint> {
+        self.paginate(NotesEndpoint {
+            blog_identifier: blog_identifier.into().into(),
+            post_id,
+            ..Default::default()
+        })
+    }
 }
 
 #[derive(Builder)]
-#[builder(ctor(vis = ""))]
+#[builder(
+    ctor(vis = ""),
+    into_future(output = "Result<crate::api::UserInfoResponse, RequestError>")
+)]
 pub struct UserInfoRequestBuilder {
     #[builder(set(ctor))]
     client: Client,
+    #[builder(set(setter(
+        strip_option,
+        doc = "give up on the request if it takes longer than `timeout`, overriding the client's default (see [`ClientBuilder::timeout`])"
+    )))]
+    timeout: Option<Duration>,
 }
 
 impl UserInfoRequestBuilder {
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
     pub async fn send(self) -> Result<crate::api::UserInfoResponse, RequestError> {
+        self.send_with_meta().await.map(|r| r.payload)
+    }
+
+    /// like [`send`][Self::send], but also returns details about the response
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send_with_meta(
+        self,
+    ) -> Result<Response<crate::api::UserInfoResponse>, RequestError> {
+        self.client
+            .inner
+            .request_with_timeout(&crate::api::UserInfoEndpoint, self.timeout)
+            .await
+    }
+
+    /// build the request without sending it, to see what would be sent
+    ///
+    /// # Errors
+    /// returns an error if the request couldn't be built, e.g. because the client's api base url
+    /// (see [`ClientBuilder::api_base_url`]) isn't a valid url
+    pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+        self.client.inner.prepare(&crate::api::UserInfoEndpoint)
+    }
+}
+
+#[derive(Builder)]
+#[builder(
+    ctor(vis = ""),
+    into_future(output = "Result<crate::api::BlogInfoResponse, RequestError>")
+)]
+pub struct BlogInfoRequestBuilder {
+    #[builder(set(ctor))]
+    client: Client,
+    #[builder(set(ctor))]
+    blog_identifier: Box<str>,
+    #[builder(set(setter(
+        strip_option,
+        doc = "give up on the request if it takes longer than `timeout`, overriding the client's default (see [`ClientBuilder::timeout`])"
+    )))]
+    timeout: Option<Duration>,
+}
+
+impl BlogInfoRequestBuilder {
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send(self) -> Result<crate::api::BlogInfoResponse, RequestError> {
+        self.send_with_meta().await.map(|r| r.payload)
+    }
+
+    /// like [`send`][Self::send], but also returns details about the response
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send_with_meta(
+        self,
+    ) -> Result<Response<crate::api::BlogInfoResponse>, RequestError> {
+        self.client
+            .inner
+            .request_with_timeout(
+                &crate::api::BlogInfoEndpoint {
+                    blog_identifier: self.blog_identifier.into(),
+                },
+                self.timeout,
+            )
+            .await
+    }
+
+    /// build the request without sending it, to see what would be sent
+    ///
+    /// # Errors
+    /// returns an error if the request couldn't be built, e.g. because the blog identifier made
+    /// for an invalid url
+    pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+        self.client.inner.prepare(&crate::api::BlogInfoEndpoint {
+            blog_identifier: self.blog_identifier.into(),
+        })
+    }
+}
+
+#[derive(Builder)]
+#[builder(
+    ctor(vis = ""),
+    into_future(output = "Result<crate::api::AvatarResponse, RequestError>")
+)]
+pub struct AvatarRequestBuilder {
+    #[builder(set(ctor))]
+    client: Client,
+    #[builder(set(ctor))]
+    blog_identifier: Box<str>,
+    #[builder(set(setter(
+        strip_option,
+        doc = "the avatar's width (and height) in pixels, see [`AvatarEndpoint::size`][crate::api::AvatarEndpoint::size]"
+    )))]
+    size: Option<u16>,
+    #[builder(set(setter(
+        strip_option,
+        doc = "give up on the request if it takes longer than `timeout`, overriding the client's default (see [`ClientBuilder::timeout`])"
+    )))]
+    timeout: Option<Duration>,
+}
+
+impl AvatarRequestBuilder {
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send(self) -> Result<crate::api::AvatarResponse, RequestError> {
+        self.send_with_meta().await.map(|r| r.payload)
+    }
+
+    /// like [`send`][Self::send], but also returns details about the response
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send_with_meta(
+        self,
+    ) -> Result<Response<crate::api::AvatarResponse>, RequestError> {
         self.client
             .inner
-            .do_request(
-                reqwest::Method::GET,
-                "https://api.tumblr.com/v2/user/info",
-                Option::<String>::None,
-                None,
+            .request_with_timeout(
+                &crate::api::AvatarEndpoint {
+                    blog_identifier: self.blog_identifier.into(),
+                    size: self.size,
+                },
+                self.timeout,
             )
             .await
-            .map(|r| r.response)
+    }
+
+    /// build the request without sending it, to see what would be sent
+    ///
+    /// # Errors
+    /// returns an error if the request couldn't be built, e.g. because the blog identifier made
+    /// for an invalid url
+    pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+        self.client.inner.prepare(&crate::api::AvatarEndpoint {
+            blog_identifier: self.blog_identifier.into(),
+            size: self.size,
+        })
+    }
+}
+
+#[derive(Builder)]
+#[builder(
+    ctor(vis = ""),
+    into_future(output = "Result<crate::api::NPFPost, RequestError>")
+)]
+pub struct PostRequestBuilder {
+    #[builder(set(ctor))]
+    client: Client,
+    #[builder(set(ctor))]
+    blog_identifier: Box<str>,
+    #[builder(set(ctor))]
+    post_id: i64,
+    #[builder(set(setter(
+        strip_option,
+        doc = "give up on the request if it takes longer than `timeout`, overriding the client's default (see [`ClientBuilder::timeout`])"
+    )))]
+    timeout: Option<Duration>,
+}
+
+impl PostRequestBuilder {
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send(self) -> Result<crate::api::NPFPost, RequestError> {
+        self.send_with_meta().await.map(|r| r.payload)
+    }
+
+    /// like [`send`][Self::send], but also returns details about the response
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send_with_meta(self) -> Result<Response<crate::api::NPFPost>, RequestError> {
+        self.client
+            .inner
+            .request_with_timeout(
+                &crate::api::PostEndpoint {
+                    blog_identifier: self.blog_identifier.into(),
+                    post_id: self.post_id,
+                },
+                self.timeout,
+            )
+            .await
+    }
+
+    /// build the request without sending it, to see what would be sent
+    ///
+    /// # Errors
+    /// returns an error if the request couldn't be built, e.g. because the blog identifier made
+    /// for an invalid url
+    pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+        self.client.inner.prepare(&crate::api::PostEndpoint {
+            blog_identifier: self.blog_identifier.into(),
+            post_id: self.post_id,
+        })
+    }
+}
+
+/// see [`Client::raw`]
+#[derive(Builder)]
+#[builder(
+    ctor(vis = ""),
+    into_future(output = "Result<serde_json::Value, RequestError>")
+)]
+pub struct RawRequestBuilder {
+    #[builder(set(ctor))]
+    client: Client,
+    #[builder(set(ctor))]
+    method: http::Method,
+    #[builder(set(ctor))]
+    path: Box<str>,
+    #[builder(set = "no")]
+    query: Vec<(Cow<'static, str>, String)>,
+    #[builder(set(setter(
+        strip_option,
+        doc = "send `json` as the request body (or as the `json` part, if there are attachments)"
+    )))]
+    json: Option<serde_json::Value>,
+    #[builder(set = "no")]
+    attachments: Vec<crate::api::Attachment>,
+    #[builder(set(setter(
+        strip_option,
+        doc = "send exactly `body` as the request body, instead of any json or attachments"
+    )))]
+    body: Option<crate::api::RequestBody>,
+    #[builder(set(setter(
+        strip_option,
+        doc = "give up on the request if it takes longer than `timeout`, overriding the client's default (see [`ClientBuilder::timeout`])"
+    )))]
+    timeout: Option<Duration>,
+}
+
+impl RawRequestBuilder {
+    /// add a query string parameter
+    #[must_use]
+    pub fn query<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<String>,
+    {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// attach a file, sending the request as a multipart form the same way
+    /// [`CreatePostRequestBuilder::add_attachment`] does
+    #[must_use]
+    pub fn add_attachment<D, S1, S2>(mut self, data: D, mime_type: S1, identifier: S2) -> Self
+    where
+        D: Into<Bytes>,
+        S1: Into<Box<str>>,
+        S2: Into<Cow<'static, str>>,
+    {
+        self.attachments.push(crate::api::Attachment {
+            data: data.into(),
+            mime_type: mime_type.into(),
+            identifier: identifier.into(),
+        });
+        self
+    }
+
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send(self) -> Result<serde_json::Value, RequestError> {
+        self.send_as().await
+    }
+
+    /// like [`send`][Self::send], but parses the response as `T` instead of as arbitrary json
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, if the api responds with an
+    /// error, or if the response doesn't match `T`
+    pub async fn send_as<T: DeserializeOwned>(self) -> Result<T, RequestError> {
+        self.send_with_meta().await.map(|r| r.payload)
+    }
+
+    /// like [`send_as`][Self::send_as], but also returns details about the response
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, if the api responds with an
+    /// error, or if the response doesn't match `T`
+    pub async fn send_with_meta<T: DeserializeOwned>(self) -> Result<Response<T>, RequestError> {
+        let timeout = self.timeout;
+        let (client, endpoint) = self.build_endpoint()?;
+        client.inner.request_with_timeout(&endpoint, timeout).await
+    }
+
+    /// build the request without sending it, to see what would be sent
+    ///
+    /// # Errors
+    /// returns an error if the request couldn't be built, e.g. because the path made for an
+    /// invalid url
+    pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+        let (client, endpoint) = self.build_endpoint::<serde_json::Value>()?;
+        client.inner.prepare(&endpoint)
+    }
+
+    fn build_endpoint<T>(self) -> Result<(Client, crate::api::RawEndpoint<T>), RequestError> {
+        let mut endpoint = crate::api::RawEndpoint::new(self.method, self.path);
+        endpoint.query = self.query;
+        endpoint.body = match (self.body, self.json) {
+            (Some(body), _) => Some(body),
+            (None, None) if self.attachments.is_empty() => None,
+            (None, json) => Some(crate::api::RequestBody::json_with_attachments(
+                &json.unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new())),
+                &self.attachments,
+            )?),
+        };
+        Ok((self.client, endpoint))
     }
 }
 
@@ -181,7 +1518,10 @@ pub enum CreatePostState {
 
 // TODO figure out we want to expose the `date` field (and also like. what it even does lmao)
 #[derive(Builder)]
-#[builder(ctor(vis = ""))]
+#[builder(
+    ctor(vis = ""),
+    into_future(output = "Result<crate::api::CreatePostResponse, RequestError>")
+)]
 pub struct CreatePostRequestBuilder {
     #[builder(set(ctor))]
     client: Client,
@@ -202,36 +1542,132 @@ pub struct CreatePostRequestBuilder {
     source_url: Option<Box<str>>,
     // TODO need to add 'call method on it' set mode (push in this case), and add a way to set the default used explicitly
     #[builder(set = "no")]
-    attachments: Vec<CreatePostAttachment>,
-}
-
-struct CreatePostAttachment {
-    stream: reqwest::Body,
-    mime_type: Box<str>,
-    identifier: Cow<'static, str>,
+    attachments: Vec<crate::api::Attachment>,
+    #[builder(set = "no")]
+    streamed_attachments: Vec<StreamedAttachment>,
+    #[builder(set(setter(
+        strip_option,
+        doc = "give up on the request if it takes longer than `timeout`, overriding the client's default (see [`ClientBuilder::timeout`])"
+    )))]
+    timeout: Option<Duration>,
 }
 
 impl CreatePostRequestBuilder {
+    /// attach a file to upload along with the post. `identifier` is what the post's content
+    /// refers to it by (via [`npf::MediaObjectContent::Identifier`][crate::npf::MediaObjectContent::Identifier]).
+    #[must_use]
+    pub fn add_attachment<D, S1, S2>(mut self, data: D, mime_type: S1, identifier: S2) -> Self
+    where
+        D: Into<Bytes>,
+        S1: Into<Box<str>>,
+        S2: Into<Cow<'static, str>>,
+    {
+        self.attachments.push(crate::api::Attachment {
+            data: data.into(),
+            mime_type: mime_type.into(),
+            identifier: identifier.into(),
+        });
+        self
+    }
+
+    /// like [`add_attachment`][Self::add_attachment], but the file is read from `stream` as the
+    /// request is sent, rather than held in memory all at once (as long as the http backend
+    /// supports that, see [`HttpBackend::send_streaming`] - the `reqwest` one does).
+    ///
+    /// since the stream can only be read once, a request with a streamed attachment is never
+    /// retried, not even to reauthorize. [`dry_run`][Self::dry_run] describes the attachment
+    /// without reading it, so without its length.
     #[must_use]
-    pub fn add_attachment<S1, S2>(
+    pub fn add_attachment_stream<St, E, S1, S2>(
         mut self,
-        stream: reqwest::Body,
+        stream: St,
         mime_type: S1,
         identifier: S2,
     ) -> Self
     where
+        St: futures_util::Stream<Item = Result<Bytes, E>> + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
         S1: Into<Box<str>>,
         S2: Into<Cow<'static, str>>,
     {
-        self.attachments.push(CreatePostAttachment {
-            stream,
+        use futures_util::TryStreamExt;
+        self.streamed_attachments.push(StreamedAttachment {
+            stream: Box::pin(stream.map_err(Into::into)),
             mime_type: mime_type.into(),
             identifier: identifier.into(),
         });
         self
     }
 
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
     pub async fn send(self) -> Result<crate::api::CreatePostResponse, RequestError> {
+        self.send_with_meta().await.map(|r| r.payload)
+    }
+
+    /// like [`send`][Self::send], but also returns details about the response
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send_with_meta(
+        self,
+    ) -> Result<Response<crate::api::CreatePostResponse>, RequestError> {
+        let count_attachments = |kind: &str| {
+            let mime_types = self
+                .attachments
+                .iter()
+                .map(|attachment| &attachment.mime_type)
+                .chain(
+                    self.streamed_attachments
+                        .iter()
+                        .map(|attachment| &attachment.mime_type),
+                );
+            let count = mime_types
+                .filter(|mime_type| mime_type.starts_with(kind))
+                .count();
+            i64::try_from(count).unwrap_or(i64::MAX)
+        };
+        let quota_usage = [
+            (Quota::Posts, 1),
+            (Quota::Photos, count_attachments("image/")),
+            (Quota::Videos, count_attachments("video/")),
+        ];
+        let timeout = self.timeout;
+        let (client, endpoint, streamed) = self.build_endpoint()?;
+        let streamed = if streamed.is_empty() {
+            None
+        } else {
+            Some(StreamingBody::create_post(&endpoint, streamed)?)
+        };
+        client
+            .inner
+            .request_with_quota(&endpoint, &quota_usage, streamed, timeout)
+            .await
+    }
+
+    /// build the request without sending it, to see what would be sent (e.g. to preview the post)
+    ///
+    /// # Errors
+    /// returns an error if the request couldn't be built, e.g. because an attachment's mime type
+    /// is invalid
+    pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+        let (client, endpoint, streamed) = self.build_endpoint()?;
+        let mut prepared = client.inner.prepare(&endpoint)?;
+        upload::prepare_create_post(&endpoint, &streamed, &mut prepared)?;
+        Ok(prepared)
+    }
+
+    /// the endpoint to send the post to, along with any attachments to stream alongside it
+    fn build_endpoint(
+        self,
+    ) -> Result<
+        (
+            Client,
+            crate::api::CreatePostEndpoint,
+            Vec<StreamedAttachment>,
+        ),
+        RequestError,
+    > {
         // the api takes state & publish_on as two different properties,
         //  where publish_on is only valid when the state is queue & that represents a scheduled post.
         //  we instead expose it as a single enum where queue & schedule are different variants,
@@ -250,71 +1686,72 @@ impl CreatePostRequestBuilder {
             Some(CreatePostState::Schedule { publish_on }) => (
                 Some(crate::api::CreatePostState::Queue),
                 Some(
-                    // TODO handle properly instead of unwrapping
-                    // TODO also the format isn't right i think b/c these were 400.8001ing last time i checked
-                    publish_on
-                        .format(&time::format_description::well_known::Iso8601::DEFAULT)
-                        .unwrap(),
+                    // TODO the format isn't right i think b/c these were 400.8001ing last time i checked
+                    publish_on.format(&time::format_description::well_known::Iso8601::DEFAULT)?,
                 ),
             ),
         };
-        self.client
-            .inner
-            .do_request(
-                reqwest::Method::POST,
-                format!(
-                    "https://api.tumblr.com/v2/blog/{}/posts",
-                    self.blog_identifier
-                ),
-                Some(crate::api::CreatePostRequest {
-                    content: self.content,
-                    state,
-                    publish_on,
-                    date: None,
-                    tags: self.tags.map(std::convert::Into::into), // TODO
-                    source_url: self.source_url.map(std::convert::Into::into), // TODO
-                    send_to_twitter: None,
-                    is_private: None,
-                    slug: None,
-                    interactability_reblog: None,
-                }),
-                Some(
-                    self.attachments
-                        .into_iter()
-                        .map(|attachment| {
-                            let part = reqwest::multipart::Part::stream(attachment.stream)
-                                // tumblr requires a filename but doesn't actually check it so we just put something there
-                                .file_name("a")
-                                .mime_str(&attachment.mime_type)
-                                .unwrap(); // TODO handle instead of just unwrapping
-                            (attachment.identifier, part)
-                        })
-                        .collect(),
-                ),
-            )
-            .await
-            .map(|r| r.response)
+        let endpoint = crate::api::CreatePostEndpoint {
+            blog_identifier: self.blog_identifier.into(),
+            request: crate::api::CreatePostRequest {
+                content: self.content,
+                state,
+                publish_on,
+                date: None,
+                tags: self.tags.map(std::convert::Into::into), // TODO
+                source_url: self.source_url.map(std::convert::Into::into), // TODO
+                send_to_twitter: None,
+                is_private: None,
+                slug: None,
+                interactability_reblog: None,
+            },
+            attachments: self.attachments,
+        };
+        Ok((self.client, endpoint, self.streamed_attachments))
     }
 }
 
 #[derive(Builder)]
-#[builder(ctor(vis = ""))]
+#[builder(
+    ctor(vis = ""),
+    into_future(output = "Result<crate::api::LimitsResponse, RequestError>")
+)]
 pub struct ApiLimitsRequestBuilder {
     #[builder(set(ctor))]
     client: Client,
+    #[builder(set(setter(
+        strip_option,
+        doc = "give up on the request if it takes longer than `timeout`, overriding the client's default (see [`ClientBuilder::timeout`])"
+    )))]
+    timeout: Option<Duration>,
 }
 
 impl ApiLimitsRequestBuilder {
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
     pub async fn send(self) -> Result<crate::api::LimitsResponse, RequestError> {
+        self.send_with_meta().await.map(|r| r.payload)
+    }
+
+    /// like [`send`][Self::send], but also returns details about the response
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+    pub async fn send_with_meta(
+        self,
+    ) -> Result<Response<crate::api::LimitsResponse>, RequestError> {
         self.client
             .inner
-            .do_request(
-                reqwest::Method::GET,
-                "https://api.tumblr.com/v2/user/limits",
-                Option::<String>::None,
-                None,
-            )
+            .request_with_timeout(&crate::api::UserLimitsEndpoint, self.timeout)
             .await
-            .map(|r| r.response)
+    }
+
+    /// build the request without sending it, to see what would be sent
+    ///
+    /// # Errors
+    /// returns an error if the request couldn't be built, e.g. because the client's api base url
+    /// (see [`ClientBuilder::api_base_url`]) isn't a valid url
+    pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+        self.client.inner.prepare(&crate::api::UserLimitsEndpoint)
     }
 }
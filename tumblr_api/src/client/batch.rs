@@ -0,0 +1,352 @@
+//! running lots of small operations on posts (liking, reblogging, deleting, retagging) at once.
+
+use std::{borrow::Cow, sync::Arc, time::Duration};
+
+use futures_util::{stream, StreamExt};
+
+use super::{ClientInner, Quota, RequestError};
+use crate::api::{
+    BlogInfoEndpoint, DeletePostEndpoint, EditTagsEndpoint, Endpoint, LikeEndpoint, PostEndpoint,
+    ReblogEndpoint, ReblogRequest,
+};
+
+/// how many operations a [`Batch`] runs at once, unless set otherwise via [`Batch::concurrency`]
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// one thing for a [`Batch`] to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// like a post
+    Like {
+        post_id: i64,
+        /// the post's [`reblog_key`][crate::api::NPFPost::reblog_key]
+        reblog_key: String,
+    },
+    /// reblog a post to one of the user's blogs
+    Reblog {
+        /// the blog to reblog the post to
+        blog_identifier: String,
+        /// the uuid of the blog the post is being reblogged from
+        parent_blog_uuid: String,
+        parent_post_id: i64,
+        /// the post's [`reblog_key`][crate::api::NPFPost::reblog_key]
+        reblog_key: String,
+        /// comma-separated tags to add to the reblog
+        tags: Option<String>,
+    },
+    /// delete one of the user's posts
+    Delete {
+        blog_identifier: String,
+        post_id: i64,
+    },
+    /// replace the tags on one of the user's posts. (since the api can only replace a whole post
+    /// at once, this fetches the post first, to send its content back along with the new tags.)
+    EditTags {
+        blog_identifier: String,
+        post_id: i64,
+        /// the post's new tags, comma-separated
+        tags: String,
+    },
+}
+
+/// what a successful [`Operation`] did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperationOutput {
+    Liked,
+    Reblogged {
+        /// the id of the new post
+        post_id: String,
+    },
+    Deleted,
+    TagsEdited,
+}
+
+/// an [`Operation`], along with how it went
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub operation: Operation,
+    pub result: Result<OperationOutput, RequestError>,
+}
+
+/// the results of running a [`Batch`], in the same order as its operations were added
+#[derive(Debug)]
+pub struct BatchReport {
+    pub entries: Vec<BatchEntry>,
+}
+
+impl BatchReport {
+    /// whether every operation succeeded
+    #[must_use]
+    pub fn all_succeeded(&self) -> bool {
+        self.entries.iter().all(|entry| entry.result.is_ok())
+    }
+
+    /// the operations that failed, along with their positions in the batch
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &Operation, &RequestError)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                entry
+                    .result
+                    .as_ref()
+                    .err()
+                    .map(|err| (index, &entry.operation, err))
+            })
+    }
+}
+
+/// a set of [`Operation`]s to run together, created via [`Client::batch`][super::Client::batch].
+///
+/// operations are run a few at a time (see [`concurrency`][Batch::concurrency]). each one waits
+/// for the client's [rate limits][super::Client::rate_limit] to allow it, and goes through the
+/// [quota governor][super::ClientBuilder::quota_governor] if there is one, so a big batch won't
+/// blow through either. one operation failing doesn't stop the others.
+///
+/// # Examples
+/// ```no_run
+/// use tumblr_api::{auth::Credentials, client::{batch::Operation, Client}};
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let client = Client::new(Credentials::new("your consumer key", "your consumer secret"));
+/// let report = client
+///     .batch()
+///     .push(Operation::Like {
+///         post_id: 1234,
+///         reblog_key: "abcd".into(),
+///     })
+///     .push(Operation::Delete {
+///         blog_identifier: "my-blog".into(),
+///         post_id: 5678,
+///     })
+///     .run()
+///     .await;
+/// for (index, operation, err) in report.failures() {
+///     eprintln!("operation {index} ({operation:?}) failed: {err}");
+/// }
+/// # }
+/// ```
+#[must_use = "batches do nothing unless `run`"]
+pub struct Batch {
+    client: Arc<ClientInner>,
+    operations: Vec<Operation>,
+    concurrency: usize,
+    timeout: Option<Duration>,
+}
+
+impl Batch {
+    pub(super) const fn new(client: Arc<ClientInner>) -> Self {
+        Self {
+            client,
+            operations: Vec::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+            timeout: None,
+        }
+    }
+
+    /// add an operation to the end of the batch
+    pub fn push(mut self, operation: Operation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// add several operations to the end of the batch
+    pub fn operations<I: IntoIterator<Item = Operation>>(mut self, operations: I) -> Self {
+        self.operations.extend(operations);
+        self
+    }
+
+    /// how many operations to run at once. defaults to [`DEFAULT_CONCURRENCY`]. (0 is treated
+    /// as 1.)
+    pub const fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = if concurrency == 0 { 1 } else { concurrency };
+        self
+    }
+
+    /// give up on each request an operation makes if it takes longer than `timeout`, overriding
+    /// the client's default (see [`ClientBuilder::timeout`][super::ClientBuilder::timeout])
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// run every operation, returning once they've all finished
+    pub async fn run(self) -> BatchReport {
+        let client = &self.client;
+        let timeout = self.timeout;
+        let entries = stream::iter(self.operations)
+            .map(|operation| async move {
+                let result = run_operation(client, &operation, timeout).await;
+                BatchEntry { operation, result }
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+        BatchReport { entries }
+    }
+}
+
+async fn run_operation(
+    client: &ClientInner,
+    operation: &Operation,
+    timeout: Option<Duration>,
+) -> Result<OperationOutput, RequestError> {
+    let wait = client
+        .rate_limit()
+        .map(|rate_limit| rate_limit.time_until_available())
+        .unwrap_or_default();
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+    match operation {
+        Operation::Like {
+            post_id,
+            reblog_key,
+        } => {
+            client
+                .request_with_quota(
+                    &LikeEndpoint {
+                        post_id: *post_id,
+                        reblog_key: reblog_key.clone(),
+                    },
+                    &[(Quota::Likes, 1)],
+                    None,
+                    timeout,
+                )
+                .await?;
+            Ok(OperationOutput::Liked)
+        }
+        Operation::Reblog {
+            blog_identifier,
+            parent_blog_uuid,
+            parent_post_id,
+            reblog_key,
+            tags,
+        } => {
+            let response = client
+                .request_with_quota(
+                    &ReblogEndpoint {
+                        blog_identifier: blog_identifier.clone(),
+                        request: ReblogRequest {
+                            parent_tumblelog_uuid: parent_blog_uuid.clone(),
+                            parent_post_id: parent_post_id.to_string(),
+                            reblog_key: reblog_key.clone(),
+                            content: Vec::new(),
+                            tags: tags.clone(),
+                            state: None,
+                        },
+                    },
+                    &[(Quota::Posts, 1)],
+                    None,
+                    timeout,
+                )
+                .await?;
+            Ok(OperationOutput::Reblogged {
+                post_id: response.payload.id,
+            })
+        }
+        Operation::Delete {
+            blog_identifier,
+            post_id,
+        } => {
+            client
+                .request_with_timeout(
+                    &DeletePostEndpoint {
+                        blog_identifier: blog_identifier.clone(),
+                        post_id: *post_id,
+                    },
+                    timeout,
+                )
+                .await?;
+            invalidate_post(client, blog_identifier, *post_id).await;
+            Ok(OperationOutput::Deleted)
+        }
+        Operation::EditTags {
+            blog_identifier,
+            post_id,
+            tags,
+        } => {
+            edit_tags(client, blog_identifier, *post_id, tags, timeout).await?;
+            invalidate_post(client, blog_identifier, *post_id).await;
+            Ok(OperationOutput::TagsEdited)
+        }
+    }
+}
+
+/// fetch a post and send its content back with `tags` instead of the ones it has
+async fn edit_tags(
+    client: &ClientInner,
+    blog_identifier: &str,
+    post_id: i64,
+    tags: &str,
+    timeout: Option<Duration>,
+) -> Result<(), RequestError> {
+    // (not from the cache, which might not have caught up with an edit made elsewhere)
+    let post = client
+        .request_with_timeout(
+            &Uncached(PostEndpoint {
+                blog_identifier: blog_identifier.to_string(),
+                post_id,
+            }),
+            timeout,
+        )
+        .await?
+        .payload;
+    client
+        .request_with_timeout(
+            &EditTagsEndpoint {
+                blog_identifier: blog_identifier.to_string(),
+                post_id,
+                content: post.content,
+                layout: post.layout,
+                tags: tags.to_string(),
+            },
+            timeout,
+        )
+        .await?;
+    Ok(())
+}
+
+/// forget any cached copy of a post that's just been changed, along with its blog's info (which
+/// counts its posts, among other things)
+async fn invalidate_post(client: &ClientInner, blog_identifier: &str, post_id: i64) {
+    client
+        .invalidate_cached(&PostEndpoint {
+            blog_identifier: blog_identifier.to_string(),
+            post_id,
+        })
+        .await;
+    client
+        .invalidate_cached(&BlogInfoEndpoint {
+            blog_identifier: blog_identifier.to_string(),
+        })
+        .await;
+}
+
+/// `E`, but never served from the client's response cache
+struct Uncached<E>(E);
+
+impl<E: Endpoint> Endpoint for Uncached<E> {
+    type Response = E::Response;
+    const PATH_TEMPLATE: &'static str = E::PATH_TEMPLATE;
+
+    fn method(&self) -> http::Method {
+        self.0.method()
+    }
+
+    fn path(&self) -> Cow<'_, str> {
+        self.0.path()
+    }
+
+    fn blog_identifier(&self) -> Option<&str> {
+        self.0.blog_identifier()
+    }
+
+    fn query(&self) -> Vec<(Cow<'static, str>, String)> {
+        self.0.query()
+    }
+
+    fn body(&self) -> Result<Option<crate::api::RequestBody>, crate::api::EndpointError> {
+        self.0.body()
+    }
+}
@@ -0,0 +1,649 @@
+//! a synchronous version of [`Client`][super::Client], for code that isn't async.
+//!
+//! this wraps the async client along with a small internal runtime that requests are run on, so
+//! it has all the same settings and behaves the same way - `send` just blocks until the response
+//! arrives instead of returning a future.
+//!
+//! <div class="warning">
+//!
+//! don't use this from inside an async runtime (e.g. from within a `#[tokio::main]` function) -
+//! blocking on a request there will panic. use the async client instead.
+//! </div>
+//!
+//! # Examples
+//! ```no_run
+//! use tumblr_api::{auth::Credentials, client::blocking::Client, npf};
+//! # fn main() -> anyhow::Result<()> {
+//! let client = Client::new(Credentials::new("your consumer key", "your consumer secret"))?;
+//! let user_info = client.user_info().send()?;
+//! client
+//!     .create_post(
+//!         user_info.user.name,
+//!         vec![npf::ContentBlockText::builder("hello world").build()],
+//!     )
+//!     .tags("tag_1,tag_2")
+//!     .send()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! paginated results are plain iterators, fetching each page as it's needed
+//! ```no_run
+//! # use tumblr_api::{auth::Credentials, client::blocking::Client};
+//! # fn main() -> anyhow::Result<()> {
+//! # let client = Client::new(Credentials::new("your consumer key", "your consumer secret"))?;
+//! for post in client.blog_posts("staff").limit(50).items() {
+//!     println!("{}", post?.post_url);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{path::PathBuf, pin::Pin, sync::Arc};
+
+use futures_util::{Stream, StreamExt};
+
+use super::{
+    batch::{BatchReport, Operation},
+    cache::ResponseCache,
+    cassette::Cassette,
+    CreatePostState, Middleware, QuotaGovernor, RateLimitInfo, RequestError, Response, RetryPolicy,
+};
+use crate::{
+    api::{
+        BlogLikesEndpoint, BlogPostsEndpoint, Cursor, DashboardEndpoint, DraftsEndpoint,
+        FollowersEndpoint, NotesEndpoint, Page, Paginated,
+    },
+    auth::Credentials,
+    backend::HttpBackend,
+};
+
+/// a synchronous [`Client`][super::Client]. cheap to clone, like the async one.
+#[derive(Clone)]
+pub struct Client {
+    inner: super::Client,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+/// builder for a blocking [`Client`], see [`ClientBuilder`][super::ClientBuilder] for what each
+/// setting does.
+pub struct ClientBuilder {
+    inner: super::ClientBuilder,
+}
+
+impl ClientBuilder {
+    /// see [`ClientBuilder::backend`][super::ClientBuilder::backend]
+    #[must_use]
+    pub fn backend<B: HttpBackend + 'static>(self, backend: B) -> Self {
+        Self {
+            inner: self.inner.backend(backend),
+        }
+    }
+
+    /// see [`ClientBuilder::http_client`][super::ClientBuilder::http_client]
+    #[must_use]
+    pub fn http_client(self, http_client: reqwest::Client) -> Self {
+        Self {
+            inner: self.inner.http_client(http_client),
+        }
+    }
+
+    /// see [`ClientBuilder::api_base_url`][super::ClientBuilder::api_base_url]
+    #[must_use]
+    pub fn api_base_url<S: Into<Box<str>>>(self, api_base_url: S) -> Self {
+        Self {
+            inner: self.inner.api_base_url(api_base_url),
+        }
+    }
+
+    /// see [`ClientBuilder::oauth_token_url`][super::ClientBuilder::oauth_token_url]
+    #[must_use]
+    pub fn oauth_token_url<S: Into<Box<str>>>(self, oauth_token_url: S) -> Self {
+        Self {
+            inner: self.inner.oauth_token_url(oauth_token_url),
+        }
+    }
+
+    /// see [`ClientBuilder::record_cassette`][super::ClientBuilder::record_cassette]
+    #[must_use]
+    pub fn record_cassette<P: Into<PathBuf>>(self, path: P) -> Self {
+        Self {
+            inner: self.inner.record_cassette(path),
+        }
+    }
+
+    /// see [`ClientBuilder::replay_cassette`][super::ClientBuilder::replay_cassette]
+    #[must_use]
+    pub fn replay_cassette(self, cassette: Cassette) -> Self {
+        Self {
+            inner: self.inner.replay_cassette(cassette),
+        }
+    }
+
+    /// see [`ClientBuilder::retry_policy`][super::ClientBuilder::retry_policy]
+    #[must_use]
+    pub fn retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            inner: self.inner.retry_policy(retry_policy),
+        }
+    }
+
+    /// see [`ClientBuilder::quota_governor`][super::ClientBuilder::quota_governor]
+    #[must_use]
+    pub fn quota_governor(self, quota_governor: QuotaGovernor) -> Self {
+        Self {
+            inner: self.inner.quota_governor(quota_governor),
+        }
+    }
+
+    /// see [`ClientBuilder::keep_unparsable_bodies`][super::ClientBuilder::keep_unparsable_bodies]
+    #[must_use]
+    pub fn keep_unparsable_bodies(self, keep_unparsable_bodies: bool) -> Self {
+        Self {
+            inner: self.inner.keep_unparsable_bodies(keep_unparsable_bodies),
+        }
+    }
+
+    /// see [`ClientBuilder::middleware`][super::ClientBuilder::middleware]
+    #[must_use]
+    pub fn middleware<M: Middleware + 'static>(self, middleware: M) -> Self {
+        Self {
+            inner: self.inner.middleware(middleware),
+        }
+    }
+
+    /// see [`ClientBuilder::cache`][super::ClientBuilder::cache]
+    #[must_use]
+    pub fn cache(self, cache: ResponseCache) -> Self {
+        Self {
+            inner: self.inner.cache(cache),
+        }
+    }
+
+    /// see [`ClientBuilder::timeout`][super::ClientBuilder::timeout]
+    #[must_use]
+    pub fn timeout(self, timeout: std::time::Duration) -> Self {
+        Self {
+            inner: self.inner.timeout(timeout),
+        }
+    }
+
+    /// # Errors
+    /// returns an error if the client's internal runtime can't be started
+    pub fn build(self) -> std::io::Result<Client> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Client {
+            inner: self.inner.build(),
+            runtime: Arc::new(runtime),
+        })
+    }
+}
+
+impl Client {
+    /// # Errors
+    /// returns an error if the client's internal runtime can't be started
+    pub fn new(credentials: Credentials) -> std::io::Result<Self> {
+        Self::builder(credentials).build()
+    }
+
+    /// create a [`ClientBuilder`], for configuring a client beyond just its credentials
+    #[must_use]
+    pub fn builder(credentials: Credentials) -> ClientBuilder {
+        ClientBuilder {
+            inner: super::Client::builder(credentials),
+        }
+    }
+
+    /// create a [`ClientBuilder`] for a client that sends requests through `backend`, see the
+    /// [`backend`][crate::backend] module
+    #[must_use]
+    pub fn builder_with_backend<B: HttpBackend + 'static>(
+        credentials: Credentials,
+        backend: B,
+    ) -> ClientBuilder {
+        ClientBuilder {
+            inner: super::Client::builder_with_backend(credentials, backend),
+        }
+    }
+
+    /// the async client this wraps
+    #[must_use]
+    pub const fn as_async(&self) -> &super::Client {
+        &self.inner
+    }
+
+    /// see [`Client::rate_limit`][super::Client::rate_limit]
+    #[must_use]
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        self.inner.rate_limit()
+    }
+
+    /// see [`Client::save_cassette`][super::Client::save_cassette]
+    ///
+    /// # Errors
+    /// returns an error if the cassette file can't be written
+    pub fn save_cassette(&self) -> Result<(), super::cassette::CassetteError> {
+        self.inner.save_cassette()
+    }
+
+    #[must_use]
+    pub fn user_info(&self) -> UserInfoRequestBuilder {
+        UserInfoRequestBuilder {
+            inner: self.inner.user_info(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn create_post<B, C>(&self, blog_identifier: B, content: C) -> CreatePostRequestBuilder
+    where
+        B: Into<Box<str>>,
+        C: Into<Vec<crate::npf::ContentBlock>>,
+    {
+        CreatePostRequestBuilder {
+            inner: self.inner.create_post(blog_identifier, content),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn blog_info<B: Into<Box<str>>>(&self, blog_identifier: B) -> BlogInfoRequestBuilder {
+        BlogInfoRequestBuilder {
+            inner: self.inner.blog_info(blog_identifier),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn avatar<B: Into<Box<str>>>(&self, blog_identifier: B) -> AvatarRequestBuilder {
+        AvatarRequestBuilder {
+            inner: self.inner.avatar(blog_identifier),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn post<B: Into<Box<str>>>(&self, blog_identifier: B, post_id: i64) -> PostRequestBuilder {
+        PostRequestBuilder {
+            inner: self.inner.post(blog_identifier, post_id),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn api_limits(&self) -> ApiLimitsRequestBuilder {
+        ApiLimitsRequestBuilder {
+            inner: self.inner.api_limits(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// see [`Client::raw`][super::Client::raw]
+    #[must_use]
+    pub fn raw<P: Into<Box<str>>>(&self, method: http::Method, path: P) -> RawRequestBuilder {
+        RawRequestBuilder {
+            inner: self.inner.raw(method, path),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// see [`Client::batch`][super::Client::batch]
+    pub fn batch(&self) -> Batch {
+        Batch {
+            inner: self.inner.batch(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// see [`Client::paginate`][super::Client::paginate]
+    pub fn paginate<E>(&self, endpoint: E) -> Paginator<E>
+    where
+        E: Paginated + Send + Sync + 'static,
+        E::Response: Send,
+        E::Item: Send + 'static,
+    {
+        self.blocking_paginator(self.inner.paginate(endpoint))
+    }
+
+    /// see [`Client::blog_posts`][super::Client::blog_posts]
+    pub fn blog_posts<B: Into<Box<str>>>(
+        &self,
+        blog_identifier: B,
+    ) -> Paginator<BlogPostsEndpoint> {
+        self.blocking_paginator(self.inner.blog_posts(blog_identifier))
+    }
+
+    /// see [`Client::drafts`][super::Client::drafts]
+    pub fn drafts<B: Into<Box<str>>>(&self, blog_identifier: B) -> Paginator<DraftsEndpoint> {
+        self.blocking_paginator(self.inner.drafts(blog_identifier))
+    }
+
+    /// see [`Client::blog_likes`][super::Client::blog_likes]
+    pub fn blog_likes<B: Into<Box<str>>>(
+        &self,
+        blog_identifier: B,
+    ) -> Paginator<BlogLikesEndpoint> {
+        self.blocking_paginator(self.inner.blog_likes(blog_identifier))
+    }
+
+    /// see [`Client::followers`][super::Client::followers]
+    pub fn followers<B: Into<Box<str>>>(&self, blog_identifier: B) -> Paginator<FollowersEndpoint> {
+        self.blocking_paginator(self.inner.followers(blog_identifier))
+    }
+
+    /// see [`Client::dashboard`][super::Client::dashboard]
+    pub fn dashboard(&self) -> Paginator<DashboardEndpoint> {
+        self.blocking_paginator(self.inner.dashboard())
+    }
+
+    /// see [`Client::notes`][super::Client::notes]
+    pub fn notes<B: Into<Box<str>>>(
+        &self,
+        blog_identifier: B,
+        post_id: i64,
+    ) -> Paginator<NotesEndpoint> {
+        self.blocking_paginator(self.inner.notes(blog_identifier, post_id))
+    }
+
+    fn blocking_paginator<E>(&self, inner: super::Paginator<E>) -> Paginator<E> {
+        Paginator {
+            inner,
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+/// blocking version of [`Paginator`][super::Paginator], whose results are iterators rather than
+/// streams
+#[must_use = "paginators do nothing unless iterated over"]
+pub struct Paginator<E> {
+    inner: super::Paginator<E>,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl<E> Paginator<E>
+where
+    E: Paginated + Send + Sync + 'static,
+    E::Response: Send,
+    E::Item: Send + 'static,
+{
+    /// see [`Paginator::limit`][super::Paginator::limit]
+    pub fn limit(self, limit: usize) -> Self {
+        Self {
+            inner: self.inner.limit(limit),
+            ..self
+        }
+    }
+
+    /// see [`Paginator::timeout`][super::Paginator::timeout]
+    pub fn timeout(self, timeout: std::time::Duration) -> Self {
+        Self {
+            inner: self.inner.timeout(timeout),
+            ..self
+        }
+    }
+
+    /// see [`Paginator::resume_from`][super::Paginator::resume_from]
+    pub fn resume_from(self, cursor: Cursor) -> Self {
+        Self {
+            inner: self.inner.resume_from(cursor),
+            ..self
+        }
+    }
+
+    /// each page of results, along with the cursor for the page after it
+    pub fn pages(self) -> impl Iterator<Item = Result<Page<E::Item>, RequestError>> {
+        BlockingIter {
+            stream: Box::pin(self.inner.pages()),
+            runtime: self.runtime,
+        }
+    }
+
+    /// every item, across all the pages
+    pub fn items(self) -> impl Iterator<Item = Result<E::Item, RequestError>> {
+        BlockingIter {
+            stream: Box::pin(self.inner.items()),
+            runtime: self.runtime,
+        }
+    }
+}
+
+/// a stream, turned into an iterator by blocking on each item
+struct BlockingIter<T> {
+    stream: Pin<Box<dyn Stream<Item = T> + Send>>,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl<T> Iterator for BlockingIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+/// blocking version of [`Batch`][super::batch::Batch]
+#[must_use = "batches do nothing unless `run`"]
+pub struct Batch {
+    inner: super::batch::Batch,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl Batch {
+    /// see [`Batch::push`][super::batch::Batch::push]
+    pub fn push(self, operation: Operation) -> Self {
+        Self {
+            inner: self.inner.push(operation),
+            ..self
+        }
+    }
+
+    /// see [`Batch::operations`][super::batch::Batch::operations]
+    pub fn operations<I: IntoIterator<Item = Operation>>(self, operations: I) -> Self {
+        Self {
+            inner: self.inner.operations(operations),
+            ..self
+        }
+    }
+
+    /// see [`Batch::concurrency`][super::batch::Batch::concurrency]
+    pub fn concurrency(self, concurrency: usize) -> Self {
+        Self {
+            inner: self.inner.concurrency(concurrency),
+            ..self
+        }
+    }
+
+    /// see [`Batch::timeout`][super::batch::Batch::timeout]
+    pub fn timeout(self, timeout: std::time::Duration) -> Self {
+        Self {
+            inner: self.inner.timeout(timeout),
+            ..self
+        }
+    }
+
+    /// run every operation, returning once they've all finished
+    #[must_use]
+    pub fn run(self) -> BatchReport {
+        self.runtime.block_on(self.inner.run())
+    }
+}
+
+/// defines a blocking wrapper around one of the async request builders, with `send` and
+/// `send_with_meta` methods that block on the async ones, plus any other methods given
+macro_rules! blocking_request_builder {
+    ($name:ident, $response:ty $(, { $($method:item)* })?) => {
+        #[doc = concat!("blocking version of [`", stringify!($name), "`][super::", stringify!($name), "]")]
+        pub struct $name {
+            inner: super::$name,
+            runtime: Arc<tokio::runtime::Runtime>,
+        }
+
+        impl $name {
+            /// # Errors
+            /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+            pub fn send(self) -> Result<$response, RequestError> {
+                self.runtime.block_on(self.inner.send())
+            }
+
+            /// like [`send`][Self::send], but also returns details about the response
+            ///
+            /// # Errors
+            /// returns an error if authorizing or sending the request fails, or if the api responds with an error
+            pub fn send_with_meta(self) -> Result<Response<$response>, RequestError> {
+                self.runtime.block_on(self.inner.send_with_meta())
+            }
+
+            /// build the request without sending it, see [`PreparedRequest`][crate::api::PreparedRequest]
+            ///
+            /// # Errors
+            /// returns an error if the request couldn't be built
+            pub fn dry_run(self) -> Result<crate::api::PreparedRequest, RequestError> {
+                self.inner.dry_run()
+            }
+
+            /// give up on the request if it takes longer than `timeout`, overriding the client's
+            /// default (see [`ClientBuilder::timeout`])
+            #[must_use]
+            pub fn timeout(self, timeout: std::time::Duration) -> Self {
+                Self {
+                    inner: self.inner.timeout(timeout),
+                    ..self
+                }
+            }
+
+            $($($method)*)?
+        }
+    };
+}
+
+blocking_request_builder!(UserInfoRequestBuilder, crate::api::UserInfoResponse);
+blocking_request_builder!(ApiLimitsRequestBuilder, crate::api::LimitsResponse);
+blocking_request_builder!(BlogInfoRequestBuilder, crate::api::BlogInfoResponse);
+blocking_request_builder!(AvatarRequestBuilder, crate::api::AvatarResponse, {
+    #[must_use]
+    pub fn size(self, size: u16) -> Self {
+        Self {
+            inner: self.inner.size(size),
+            ..self
+        }
+    }
+});
+blocking_request_builder!(PostRequestBuilder, crate::api::NPFPost);
+
+blocking_request_builder!(RawRequestBuilder, serde_json::Value, {
+    #[must_use]
+    pub fn query<K, V>(self, key: K, value: V) -> Self
+    where
+        K: Into<std::borrow::Cow<'static, str>>,
+        V: Into<String>,
+    {
+        Self {
+            inner: self.inner.query(key, value),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn json(self, json: serde_json::Value) -> Self {
+        Self {
+            inner: self.inner.json(json),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn body(self, body: crate::api::RequestBody) -> Self {
+        Self {
+            inner: self.inner.body(body),
+            ..self
+        }
+    }
+
+    /// see [`RawRequestBuilder::add_attachment`][super::RawRequestBuilder::add_attachment]
+    #[must_use]
+    pub fn add_attachment<D, S1, S2>(self, data: D, mime_type: S1, identifier: S2) -> Self
+    where
+        D: Into<bytes::Bytes>,
+        S1: Into<Box<str>>,
+        S2: Into<std::borrow::Cow<'static, str>>,
+    {
+        Self {
+            inner: self.inner.add_attachment(data, mime_type, identifier),
+            ..self
+        }
+    }
+
+    /// see [`RawRequestBuilder::send_as`][super::RawRequestBuilder::send_as]
+    ///
+    /// # Errors
+    /// returns an error if authorizing or sending the request fails, if the api responds with an
+    /// error, or if the response doesn't match `T`
+    pub fn send_as<T: serde::de::DeserializeOwned>(self) -> Result<T, RequestError> {
+        self.runtime.block_on(self.inner.send_as())
+    }
+});
+
+blocking_request_builder!(CreatePostRequestBuilder, crate::api::CreatePostResponse, {
+    #[must_use]
+    pub fn tags<T: Into<Box<str>>>(self, tags: T) -> Self {
+        Self {
+            inner: self.inner.tags(tags),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn initial_state<T: Into<CreatePostState>>(self, initial_state: T) -> Self {
+        Self {
+            inner: self.inner.initial_state(initial_state),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn source_url<T: Into<Box<str>>>(self, source_url: T) -> Self {
+        Self {
+            inner: self.inner.source_url(source_url),
+            ..self
+        }
+    }
+
+    /// see [`CreatePostRequestBuilder::add_attachment`][super::CreatePostRequestBuilder::add_attachment]
+    #[must_use]
+    pub fn add_attachment<D, S1, S2>(self, data: D, mime_type: S1, identifier: S2) -> Self
+    where
+        D: Into<bytes::Bytes>,
+        S1: Into<Box<str>>,
+        S2: Into<std::borrow::Cow<'static, str>>,
+    {
+        Self {
+            inner: self.inner.add_attachment(data, mime_type, identifier),
+            ..self
+        }
+    }
+
+    /// see [`CreatePostRequestBuilder::add_attachment_stream`][super::CreatePostRequestBuilder::add_attachment_stream]
+    #[must_use]
+    pub fn add_attachment_stream<St, E, S1, S2>(
+        self,
+        stream: St,
+        mime_type: S1,
+        identifier: S2,
+    ) -> Self
+    where
+        St: futures_util::Stream<Item = Result<bytes::Bytes, E>> + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        S1: Into<Box<str>>,
+        S2: Into<std::borrow::Cow<'static, str>>,
+    {
+        Self {
+            inner: self
+                .inner
+                .add_attachment_stream(stream, mime_type, identifier),
+            ..self
+        }
+    }
+});
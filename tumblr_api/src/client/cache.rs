@@ -0,0 +1,349 @@
+//! caching responses to requests that get made over and over, like fetching a blog's info.
+//!
+//! only endpoints that opt in (via [`Endpoint::cacheable`][crate::api::Endpoint::cacheable]) are
+//! cached - currently [`blog_info`][super::Client::blog_info], [`avatar`][super::Client::avatar]
+//! and [`post`][super::Client::post].
+//! a cached response is reused without asking the api at all until its ttl is up, after which
+//! it's revalidated with an `If-None-Match` request, so if it hasn't changed the api doesn't need
+//! to send it again. deleting or retagging a post through a [batch][super::batch] forgets any
+//! cached copy of it, and of its blog's info.
+//!
+//! # Examples
+//! ```no_run
+//! use std::time::Duration;
+//! use tumblr_api::{auth::Credentials, client::{cache::ResponseCache, Client}};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), tumblr_api::client::RequestError> {
+//! let client = Client::builder(Credentials::new("your consumer key", "your consumer secret"))
+//!     .cache(ResponseCache::in_memory(1000).ttl(Duration::from_secs(30)))
+//!     .build();
+//! let info = client.blog_info("staff").send().await?;
+//! // served from the cache
+//! let info = client.blog_info("staff").send().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::RawResponse;
+use crate::backend::BoxFuture;
+
+/// how long responses are reused for before being revalidated, unless set otherwise via
+/// [`ResponseCache::ttl`]
+pub const DEFAULT_TTL: Duration = Duration::from_secs(DEFAULT_TTL_SECS);
+const DEFAULT_TTL_SECS: u64 = 5 * 60;
+
+/// a response stored in a [`CacheStore`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// the response's status, usually `200`, but `301` for [`avatar`][super::Client::avatar]
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub content_type: Option<String>,
+    /// the response's `ETag`, used to revalidate it once it's expired
+    pub etag: Option<String>,
+    /// when the response has to be revalidated before it can be used again
+    pub expires_at: SystemTime,
+}
+
+impl CachedResponse {
+    fn new(response: &RawResponse, expires_at: SystemTime) -> Self {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            status: response.status().as_u16(),
+            body: response.body().to_vec(),
+            content_type: header(http::header::CONTENT_TYPE),
+            etag: header(http::header::ETAG),
+            expires_at,
+        }
+    }
+
+    /// the response this was stored from, with `headers` (e.g. from the `304 Not Modified` it was
+    /// revalidated by) added on
+    fn to_raw(&self, headers: Option<&http::HeaderMap>) -> RawResponse {
+        let mut response = http::Response::new(bytes::Bytes::from(self.body.clone()));
+        *response.status_mut() =
+            http::StatusCode::from_u16(self.status).unwrap_or(http::StatusCode::OK);
+        let response_headers = response.headers_mut();
+        if let Some(headers) = headers {
+            response_headers.extend(
+                headers
+                    .iter()
+                    .filter(|(name, _)| *name != http::header::CONTENT_LENGTH)
+                    .map(|(name, value)| (name.clone(), value.clone())),
+            );
+        }
+        for (name, value) in [
+            (http::header::CONTENT_TYPE, &self.content_type),
+            (http::header::ETAG, &self.etag),
+        ] {
+            if let Some(value) = value
+                .as_deref()
+                .and_then(|value| http::HeaderValue::from_str(value).ok())
+            {
+                response_headers.insert(name, value);
+            }
+        }
+        response
+    }
+}
+
+/// where a [`ResponseCache`] keeps responses, e.g. in memory ([`MemoryCache`]) or in something
+/// shared between processes, like redis.
+///
+/// keys are the full url of the request (which never includes the access token), after a hash
+/// of the client's consumer key, so clients with different credentials sharing a store don't get
+/// each other's responses. the cache is best-effort, so implementations should treat any failure
+/// to read or write as a miss rather than reporting it.
+pub trait CacheStore: Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>>;
+
+    fn put<'a>(&'a self, key: &'a str, response: CachedResponse) -> BoxFuture<'a, ()>;
+
+    /// forget the response for `key`, if there is one, e.g. because a request changed it
+    fn remove<'a>(&'a self, key: &'a str) -> BoxFuture<'a, ()>;
+}
+
+impl<T: CacheStore + ?Sized> CacheStore for Arc<T> {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>> {
+        (**self).get(key)
+    }
+
+    fn put<'a>(&'a self, key: &'a str, response: CachedResponse) -> BoxFuture<'a, ()> {
+        (**self).put(key, response)
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> BoxFuture<'a, ()> {
+        (**self).remove(key)
+    }
+}
+
+/// an in-memory [`CacheStore`], which evicts the least recently used response once it's full
+pub struct MemoryCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Default)]
+struct LruState {
+    /// each response, along with when it was last used
+    entries: HashMap<String, (CachedResponse, u64)>,
+    /// keys by when they were last used
+    by_last_use: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+impl LruState {
+    /// mark `key` as just used
+    fn touch(&mut self, key: &str) {
+        self.clock += 1;
+        if let Some((_, last_used)) = self.entries.get_mut(key) {
+            self.by_last_use.remove(last_used);
+            *last_used = self.clock;
+            self.by_last_use.insert(self.clock, key.to_string());
+        }
+    }
+}
+
+impl MemoryCache {
+    /// a cache holding up to `capacity` responses
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, LruState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl CacheStore for MemoryCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>> {
+        let mut state = self.state();
+        state.touch(key);
+        let response = state.entries.get(key).map(|(response, _)| response.clone());
+        Box::pin(async move { response })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, response: CachedResponse) -> BoxFuture<'a, ()> {
+        if self.capacity > 0 {
+            let mut state = self.state();
+            // keep the old entry's place in `by_last_use` until `touch` moves it, so a key that's
+            // put again doesn't leave a stale one behind
+            let last_used = state
+                .entries
+                .get(key)
+                .map_or(0, |(_, last_used)| *last_used);
+            state.entries.insert(key.to_string(), (response, last_used));
+            state.touch(key);
+            while state.entries.len() > self.capacity {
+                let Some((_, oldest)) = state.by_last_use.pop_first() else {
+                    break;
+                };
+                state.entries.remove(&oldest);
+            }
+        }
+        Box::pin(async {})
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> BoxFuture<'a, ()> {
+        let mut state = self.state();
+        if let Some((_, last_used)) = state.entries.remove(key) {
+            state.by_last_use.remove(&last_used);
+        }
+        Box::pin(async {})
+    }
+}
+
+/// settings for caching responses, see the [module docs][self]
+#[derive(Clone)]
+pub struct ResponseCache {
+    store: Arc<dyn CacheStore>,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    /// cache responses in `store`
+    #[must_use]
+    pub fn new<S: CacheStore + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: DEFAULT_TTL,
+        }
+    }
+
+    /// cache up to `capacity` responses in memory, see [`MemoryCache`]
+    #[must_use]
+    pub fn in_memory(capacity: usize) -> Self {
+        Self::new(MemoryCache::new(capacity))
+    }
+
+    /// how long a response is reused for before it has to be revalidated. defaults to
+    /// [`DEFAULT_TTL`].
+    #[must_use]
+    pub const fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// a fresh cached response for `key`, or if there's a stale one, its `ETag` to revalidate it
+    /// with
+    pub(super) async fn lookup(&self, key: &str) -> Lookup {
+        match self.store.get(key).await {
+            Some(cached) if SystemTime::now() < cached.expires_at => {
+                Lookup::Fresh(cached.to_raw(None))
+            }
+            Some(cached) => Lookup::Stale(cached),
+            None => Lookup::Miss,
+        }
+    }
+
+    /// forget the response for `key`, so the next request for it goes to the api
+    pub(super) async fn invalidate(&self, key: &str) {
+        self.store.remove(key).await;
+    }
+
+    /// store `response` (if it's worth storing) and return it, or if it says `stale` is still
+    /// current, return that instead
+    pub(super) async fn update(
+        &self,
+        key: &str,
+        stale: Option<CachedResponse>,
+        response: RawResponse,
+    ) -> RawResponse {
+        let expires_at = SystemTime::now() + self.ttl;
+        match stale {
+            Some(mut stale) if response.status() == http::StatusCode::NOT_MODIFIED => {
+                stale.expires_at = expires_at;
+                let revalidated = stale.to_raw(Some(response.headers()));
+                self.store.put(key, stale).await;
+                revalidated
+            }
+            _ => {
+                // the api answers `avatar` with a redirect, and that's what's worth keeping
+                if matches!(
+                    response.status(),
+                    http::StatusCode::OK | http::StatusCode::MOVED_PERMANENTLY
+                ) {
+                    self.store
+                        .put(key, CachedResponse::new(&response, expires_at))
+                        .await;
+                }
+                response
+            }
+        }
+    }
+}
+
+pub(super) enum Lookup {
+    Fresh(RawResponse),
+    Stale(CachedResponse),
+    Miss,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &str) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            body: body.as_bytes().to_vec(),
+            content_type: None,
+            etag: None,
+            expires_at: SystemTime::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", response("a")).await;
+        cache.put("b", response("b")).await;
+        // using `a` makes `b` the least recently used
+        assert!(cache.get("a").await.is_some());
+        cache.put("c", response("c")).await;
+        assert!(cache.get("b").await.is_none());
+        assert_eq!(cache.get("a").await.unwrap().body, b"a");
+        assert_eq!(cache.get("c").await.unwrap().body, b"c");
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction_after_put_again() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", response("a")).await;
+        cache.put("b", response("b")).await;
+        // putting `a` again makes `b` the least recently used
+        cache.put("a", response("a2")).await;
+        cache.put("c", response("c")).await;
+        assert!(cache.get("b").await.is_none());
+        assert_eq!(cache.get("a").await.unwrap().body, b"a2");
+        assert_eq!(cache.get("c").await.unwrap().body, b"c");
+        assert_eq!(cache.state().by_last_use.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", response("a")).await;
+        cache.remove("a").await;
+        assert!(cache.get("a").await.is_none());
+        assert!(cache.state().by_last_use.is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+//! recording api interactions to a file, and replaying them back later without touching the
+//! network.
+//!
+//! # Examples
+//! recording
+//! ```no_run
+//! # use tumblr_api::{client::Client, auth::Credentials};
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let client = Client::builder(Credentials::new("your consumer key", "your consumer secret"))
+//!     .record_cassette("tests/cassettes/user_info.json")
+//!     .build();
+//! client.user_info().send().await?;
+//! // (also happens when the client is dropped, but this way errors are reported)
+//! client.save_cassette()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! replaying
+//! ```no_run
+//! # use tumblr_api::{client::Client, auth::Credentials};
+//! use tumblr_api::client::cassette::Cassette;
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let client = Client::builder(Credentials::new("unused", "unused"))
+//!     .replay_cassette(Cassette::load("tests/cassettes/user_info.json")?)
+//!     .build();
+//! // served from the cassette
+//! client.user_info().send().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Bearer tokens (and anything else that looks like a credential) are redacted before being
+//! written. Token requests aren't recorded at all, and a replaying client never authorizes.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+/// what redacted values get replaced with
+const REDACTED: &str = "[REDACTED]";
+
+/// headers whose values are always redacted
+const REDACTED_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie"];
+
+/// json fields whose values are always redacted, wherever they appear in a body
+const REDACTED_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "client_id",
+    "client_secret",
+    "consumer_key",
+    "consumer_secret",
+];
+
+#[derive(thiserror::Error, Debug)]
+pub enum CassetteError {
+    #[error("couldn't read or write cassette: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed cassette: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("cassette has no (unused) interaction for {method} {url}")]
+    NoMatchingInteraction { method: String, url: String },
+}
+
+/// a series of recorded request/response pairs
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// the request body, if it was valid utf-8. (so multipart uploads of binary files aren't
+    /// recorded.)
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Cassette {
+    /// load a cassette previously written by a recording client
+    ///
+    /// # Errors
+    /// returns an error if the file can't be read or isn't a valid cassette
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CassetteError> {
+        let data = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// write this cassette to `path`, replacing anything already there
+    ///
+    /// # Errors
+    /// returns an error if the file can't be written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CassetteError> {
+        let data = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+fn redact_headers(headers: &http::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+fn redact_body(body: &[u8]) -> String {
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+impl RecordedRequest {
+    pub(super) fn new(request: &http::Request<Bytes>) -> Self {
+        let body = request.body();
+        Self {
+            method: request.method().to_string(),
+            url: request.uri().to_string(),
+            headers: redact_headers(request.headers()),
+            body: (!body.is_empty() && std::str::from_utf8(body).is_ok())
+                .then(|| redact_body(body)),
+        }
+    }
+}
+
+impl RecordedResponse {
+    pub(super) fn new(response: &super::RawResponse) -> Self {
+        Self {
+            status: response.status().as_u16(),
+            headers: redact_headers(response.headers()),
+            body: redact_body(response.body()),
+        }
+    }
+
+    fn to_raw(&self) -> super::RawResponse {
+        let headers = self
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    http::header::HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    http::header::HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+        let mut response = super::RawResponse::new(self.body.clone().into());
+        *response.status_mut() = http::StatusCode::from_u16(self.status)
+            .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+        *response.headers_mut() = headers;
+        response
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// collects interactions in memory, writing them to the cassette file when
+/// [`Client::save_cassette`][super::Client::save_cassette] is called or the client is dropped
+pub(super) struct Recorder {
+    path: PathBuf,
+    state: Mutex<RecorderState>,
+    /// held while writing the file, so a slow write of an older snapshot can't finish after (and
+    /// overwrite) a newer one
+    writing: Mutex<()>,
+}
+
+#[derive(Default)]
+struct RecorderState {
+    cassette: Cassette,
+    /// whether anything's been recorded since the file was last written
+    unsaved: bool,
+}
+
+impl Recorder {
+    pub(super) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            state: Mutex::default(),
+            writing: Mutex::default(),
+        }
+    }
+
+    pub(super) fn record(&self, request: RecordedRequest, response: &super::RawResponse) {
+        let mut state = lock(&self.state);
+        state.cassette.interactions.push(Interaction {
+            request,
+            response: RecordedResponse::new(response),
+        });
+        state.unsaved = true;
+    }
+
+    /// write everything recorded so far to the file, if there's anything new
+    pub(super) fn save(&self) -> Result<(), CassetteError> {
+        let _writing = lock(&self.writing);
+        let data = {
+            let mut state = lock(&self.state);
+            if !state.unsaved {
+                return Ok(());
+            }
+            state.unsaved = false;
+            serde_json::to_vec_pretty(&state.cassette)
+        };
+        let result = data
+            .map_err(CassetteError::from)
+            .and_then(|data| Ok(std::fs::write(&self.path, data)?));
+        if result.is_err() {
+            lock(&self.state).unsaved = true;
+        }
+        result
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // nowhere to report this to, see `Client::save_cassette`
+        #[allow(unused_variables)]
+        if let Err(err) = self.save() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %err, "couldn't save cassette");
+        }
+    }
+}
+
+/// serves responses from a cassette. each interaction is only used once, so repeated identical
+/// requests get the responses in the order they were recorded.
+pub(super) struct Player {
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl Player {
+    pub(super) fn new(cassette: Cassette) -> Self {
+        Self {
+            interactions: Mutex::new(
+                cassette
+                    .interactions
+                    .into_iter()
+                    .map(|interaction| (interaction, false))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub(super) fn replay(
+        &self,
+        request: &http::Request<Bytes>,
+    ) -> Result<super::RawResponse, CassetteError> {
+        let method = request.method().as_str();
+        let url = request.uri().to_string();
+        let mut interactions = lock(&self.interactions);
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(interaction, used)| {
+                !used && interaction.request.method == method && interaction.request.url == url
+            })
+            .ok_or_else(|| CassetteError::NoMatchingInteraction {
+                method: method.to_string(),
+                url,
+            })?;
+        *used = true;
+        Ok(interaction.response.to_raw())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction() {
+        let mut headers = http::header::HeaderMap::new();
+        headers.insert("authorization", "Bearer abc123".parse().unwrap());
+        headers.insert("content-type", "application/json".parse().unwrap());
+        let headers = redact_headers(&headers);
+        assert!(headers.contains(&("authorization".into(), REDACTED.into())));
+        assert!(headers.contains(&("content-type".into(), "application/json".into())));
+
+        let body = redact_body(
+            br#"{"a": {"access_token": "abc123"}, "b": [{"client_secret": "x"}], "c": "d"}"#,
+        );
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"a": {"access_token": REDACTED}, "b": [{"client_secret": REDACTED}], "c": "d"})
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! keeping track of the api's daily quotas client-side, so requests that would exceed them can be
+//! held back instead of being sent and rejected.
+
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use time::OffsetDateTime;
+
+use crate::api::{LimitEntry, UserLimits};
+
+/// one of the daily quotas from [`UserLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quota {
+    Posts,
+    Likes,
+    Follows,
+    Photos,
+    Videos,
+    VideoSeconds,
+}
+
+impl Quota {
+    fn entry(self, limits: &UserLimits) -> &LimitEntry {
+        match self {
+            Self::Posts => &limits.posts,
+            Self::Likes => &limits.likes,
+            Self::Follows => &limits.follows,
+            Self::Photos => &limits.photos,
+            Self::Videos => &limits.videos,
+            Self::VideoSeconds => &limits.video_seconds,
+        }
+    }
+
+    fn entry_mut(self, limits: &mut UserLimits) -> &mut LimitEntry {
+        match self {
+            Self::Posts => &mut limits.posts,
+            Self::Likes => &mut limits.likes,
+            Self::Follows => &mut limits.follows,
+            Self::Photos => &mut limits.photos,
+            Self::Videos => &mut limits.videos,
+            Self::VideoSeconds => &mut limits.video_seconds,
+        }
+    }
+}
+
+/// what to do with a request that would exceed a quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnQuotaExhausted {
+    /// fail with [`RequestError::QuotaExhausted`][super::RequestError::QuotaExhausted] without
+    /// sending anything
+    FailFast,
+    /// wait until the quota resets, then send the request
+    Wait,
+}
+
+/// settings for tracking the api's daily quotas client-side.
+///
+/// when a client has a governor, it fetches `/user/limits` before the first request that uses up
+/// some quota (and again every [`refresh_interval`][QuotaGovernor::refresh_interval] after that),
+/// and decrements its local copy as requests are sent in between (giving the quota back if they
+/// fail). requests that would exceed a quota are then either failed immediately or held until it
+/// resets, depending on [`on_exhausted`][QuotaGovernor::on_exhausted]. quota is set aside for a
+/// request before it's sent, so concurrent requests can't overshoot a limit between them.
+///
+/// the requests this applies to are:
+/// - creating a post, which uses up one of [`Quota::Posts`], plus one [`Quota::Photos`] or
+///   [`Quota::Videos`] per image or video attachment
+/// - reblogging a post (via a [`Batch`][super::batch::Batch]), which uses up one of
+///   [`Quota::Posts`]
+/// - liking a post (via a [`Batch`][super::batch::Batch]), which uses up one of [`Quota::Likes`]
+///
+/// [`Quota::Follows`] and [`Quota::VideoSeconds`] aren't tracked, since this crate has no
+/// endpoints for following blogs, and doesn't know the length of uploaded videos.
+///
+/// ```
+/// use tumblr_api::{auth::Credentials, client::{Client, OnQuotaExhausted, QuotaGovernor}};
+/// let client = Client::builder(Credentials::new("your consumer key", "your consumer secret"))
+///     .quota_governor(QuotaGovernor::new().on_exhausted(OnQuotaExhausted::Wait))
+///     .build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaGovernor {
+    refresh_interval: Duration,
+    on_exhausted: OnQuotaExhausted,
+}
+
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 15 * 60;
+
+impl Default for QuotaGovernor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuotaGovernor {
+    /// a governor that refreshes its limits every 15 minutes and fails fast
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            refresh_interval: Duration::from_secs(DEFAULT_REFRESH_INTERVAL_SECS),
+            on_exhausted: OnQuotaExhausted::FailFast,
+        }
+    }
+
+    /// how often to re-fetch the limits from the api, to account for requests made elsewhere
+    /// (other processes, the website, etc.) with the same account
+    #[must_use]
+    pub const fn refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    #[must_use]
+    pub const fn on_exhausted(mut self, on_exhausted: OnQuotaExhausted) -> Self {
+        self.on_exhausted = on_exhausted;
+        self
+    }
+}
+
+/// the limits a governor currently knows about
+pub(super) struct GovernorState {
+    config: QuotaGovernor,
+    limits: Mutex<Option<(UserLimits, Instant)>>,
+    /// held while fetching new limits, so concurrent requests don't each fetch them
+    refreshing: async_lock::Mutex<()>,
+}
+
+/// the outcome of checking some usage against the known limits
+pub(super) enum Check {
+    /// the limits need to be (re-)fetched first
+    Stale,
+    /// the usage has been subtracted from the limits, and should be
+    /// [refunded][GovernorState::refund] if the request fails
+    Reserved,
+    Exhausted {
+        quota: Quota,
+        reset_at: OffsetDateTime,
+    },
+}
+
+impl GovernorState {
+    pub(super) fn new(config: QuotaGovernor) -> Self {
+        Self {
+            config,
+            limits: Mutex::new(None),
+            refreshing: async_lock::Mutex::new(()),
+        }
+    }
+
+    pub(super) const fn on_exhausted(&self) -> OnQuotaExhausted {
+        self.config.on_exhausted
+    }
+
+    fn limits(&self) -> MutexGuard<'_, Option<(UserLimits, Instant)>> {
+        self.limits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// check `usage` against the known limits, and if there's enough of each quota left, set it
+    /// aside
+    pub(super) fn reserve(&self, usage: &[(Quota, i64)]) -> Check {
+        let mut guard = self.limits();
+        let Some((limits, fetched_at)) = &mut *guard else {
+            return Check::Stale;
+        };
+        if fetched_at.elapsed() >= self.config.refresh_interval {
+            return Check::Stale;
+        }
+        for (quota, amount) in usage {
+            let entry = quota.entry(limits);
+            if entry.has_reset() {
+                return Check::Stale;
+            }
+            if entry.remaining < *amount {
+                return Check::Exhausted {
+                    quota: *quota,
+                    reset_at: entry.reset_at,
+                };
+            }
+        }
+        for (quota, amount) in usage {
+            quota.entry_mut(limits).remaining -= amount;
+        }
+        Check::Reserved
+    }
+
+    /// wait for any other fetch of the limits to finish. returns a guard to hold while fetching
+    /// them, if they still need fetching after that.
+    pub(super) async fn start_refresh(
+        &self,
+        usage: &[(Quota, i64)],
+    ) -> Option<async_lock::MutexGuard<'_, ()>> {
+        let guard = self.refreshing.lock().await;
+        let stale = match &*self.limits() {
+            None => true,
+            Some((limits, fetched_at)) => {
+                fetched_at.elapsed() >= self.config.refresh_interval
+                    || usage
+                        .iter()
+                        .any(|(quota, _)| quota.entry(limits).has_reset())
+            }
+        };
+        stale.then_some(guard)
+    }
+
+    pub(super) fn update(&self, limits: UserLimits) {
+        *self.limits() = Some((limits, Instant::now()));
+    }
+
+    /// forget the current limits, so they're re-fetched before the next request
+    pub(super) fn invalidate(&self) {
+        *self.limits() = None;
+    }
+
+    /// give back usage [reserved][GovernorState::reserve] for a request that failed
+    pub(super) fn refund(&self, usage: &[(Quota, i64)]) {
+        if let Some((limits, _)) = &mut *self.limits() {
+            for (quota, amount) in usage {
+                quota.entry_mut(limits).remaining += amount;
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! hooks for inspecting and modifying the requests a [`Client`][super::Client] makes.
+
+use std::{error::Error as StdError, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+
+use crate::{api::ResponseMeta, backend::TransportError};
+
+/// something that gets to see every api request a [`Client`][super::Client] makes, registered
+/// via [`ClientBuilder::middleware`][super::ClientBuilder::middleware].
+///
+/// each hook is called once per request, no matter how many times it ends up being retried.
+/// when there are multiple middlewares, they're called in the order they were registered.
+/// (requests for access tokens don't go through middleware, only requests to the api itself.)
+///
+/// # Examples
+/// a (very simple) circuit breaker, which stops sending requests after too many server errors
+/// in a row
+/// ```
+/// use std::sync::atomic::{AtomicU32, Ordering};
+/// use tumblr_api::{
+///     backend::{http, TransportError},
+///     client::{Middleware, ResponseInfo},
+/// };
+///
+/// #[derive(Default)]
+/// struct CircuitBreaker {
+///     failures: AtomicU32,
+/// }
+///
+/// impl Middleware for CircuitBreaker {
+///     fn on_request(
+///         &self,
+///         _endpoint: &'static str,
+///         request: &mut http::Request<bytes::Bytes>,
+///     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///         if self.failures.load(Ordering::Relaxed) >= 5 {
+///             return Err("circuit open".into());
+///         }
+///         request.headers_mut().insert("x-my-app", http::HeaderValue::from_static("1"));
+///         Ok(())
+///     }
+///
+///     fn on_response(&self, response: &ResponseInfo<'_>) {
+///         if response.status.is_server_error() {
+///             self.failures.fetch_add(1, Ordering::Relaxed);
+///         } else {
+///             self.failures.store(0, Ordering::Relaxed);
+///         }
+///     }
+///
+///     fn on_transport_error(&self, _endpoint: &'static str, _error: &TransportError) {
+///         self.failures.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+/// ```
+pub trait Middleware: Send + Sync {
+    /// called before a request is sent. `endpoint` is the endpoint's
+    /// [`PATH_TEMPLATE`][crate::api::Endpoint::PATH_TEMPLATE]. (the access token hasn't been added
+    /// to `request` yet at this point.)
+    ///
+    /// # Errors
+    /// returning an error stops the request from being sent, and the client returns it as a
+    /// [`RequestError::Middleware`][super::RequestError::Middleware].
+    fn on_request(
+        &self,
+        endpoint: &'static str,
+        request: &mut http::Request<Bytes>,
+    ) -> Result<(), Box<dyn StdError + Send + Sync>> {
+        let _ = (endpoint, request);
+        Ok(())
+    }
+
+    /// called once a response has been received, whether or not it was successful
+    fn on_response(&self, response: &ResponseInfo<'_>) {
+        let _ = response;
+    }
+
+    /// called if a request failed without any response being received
+    fn on_transport_error(&self, endpoint: &'static str, error: &TransportError) {
+        let _ = (endpoint, error);
+    }
+}
+
+impl<T: Middleware + ?Sized> Middleware for Arc<T> {
+    fn on_request(
+        &self,
+        endpoint: &'static str,
+        request: &mut http::Request<Bytes>,
+    ) -> Result<(), Box<dyn StdError + Send + Sync>> {
+        (**self).on_request(endpoint, request)
+    }
+
+    fn on_response(&self, response: &ResponseInfo<'_>) {
+        (**self).on_response(response);
+    }
+
+    fn on_transport_error(&self, endpoint: &'static str, error: &TransportError) {
+        (**self).on_transport_error(endpoint, error);
+    }
+}
+
+/// what a [`Middleware`] gets to see about a response
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ResponseInfo<'a> {
+    /// the endpoint's [`PATH_TEMPLATE`][crate::api::Endpoint::PATH_TEMPLATE]
+    pub endpoint: &'static str,
+    pub method: &'a http::Method,
+    pub status: http::StatusCode,
+    pub headers: &'a http::HeaderMap,
+    /// the `meta` the api included in the response body, if it could be parsed
+    pub meta: Option<&'a ResponseMeta>,
+    /// how long it took to get the response, including any retries
+    pub elapsed: Duration,
+}
@@ -0,0 +1,146 @@
+//! streaming the results of list endpoints, a page at a time.
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+
+use super::{ClientInner, RequestError};
+use crate::api::{Cursor, Page, Paginated};
+
+/// the results of a [`Paginated`] endpoint, as a [`Stream`] that fetches each page as it's
+/// needed. created via [`Client::paginate`][super::Client::paginate], or one of the shortcuts
+/// for particular endpoints like [`Client::blog_posts`][super::Client::blog_posts].
+///
+/// to stop early, either set a [`limit`][Paginator::limit] or just stop polling the stream -
+/// pages are only requested once the items before them have been used up.
+///
+/// # Examples
+/// everything a blog has posted with a particular tag
+/// ```no_run
+/// use futures_util::TryStreamExt;
+/// use tumblr_api::{api::BlogPostsEndpoint, auth::Credentials, client::Client};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), tumblr_api::client::RequestError> {
+/// # let client = Client::new(Credentials::new("your consumer key", "your consumer secret"));
+/// let posts: Vec<_> = client
+///     .paginate(BlogPostsEndpoint {
+///         blog_identifier: "staff".into(),
+///         tag: Some("tumblr tuesday".into()),
+///         ..Default::default()
+///     })
+///     .items()
+///     .try_collect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// going through a blog's posts a page at a time, keeping track of where we got to so we can pick
+/// up from there next time
+/// ```no_run
+/// use futures_util::TryStreamExt;
+/// # use tumblr_api::{auth::Credentials, client::Client};
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// # let client = Client::new(Credentials::new("your consumer key", "your consumer secret"));
+/// # let saved_cursor: Option<tumblr_api::api::Cursor> = None;
+/// let mut paginator = client.blog_posts("staff");
+/// if let Some(cursor) = saved_cursor {
+///     paginator = paginator.resume_from(cursor);
+/// }
+/// let mut pages = std::pin::pin!(paginator.pages());
+/// while let Some(page) = pages.try_next().await? {
+///     for post in page.items {
+///         // ...archive `post` somewhere
+///     }
+///     // ...save `page.next` somewhere
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "paginators do nothing unless turned into a stream"]
+pub struct Paginator<E> {
+    client: Arc<ClientInner>,
+    endpoint: E,
+    limit: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+impl<E> Paginator<E>
+where
+    E: Paginated + Send + Sync + 'static,
+    E::Response: Send,
+    E::Item: Send + 'static,
+{
+    pub(super) const fn new(client: Arc<ClientInner>, endpoint: E) -> Self {
+        Self {
+            client,
+            endpoint,
+            limit: None,
+            timeout: None,
+        }
+    }
+
+    /// stop after `limit` items. (if this cuts a page short, that page's
+    /// [`next`][Page::next] points at the first item that wasn't yielded, so resuming from it
+    /// picks up where this left off - see [`Paginated::cursor_within_page`].)
+    pub const fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// give up on each page's request if it takes longer than `timeout`, overriding the client's
+    /// default (see [`ClientBuilder::timeout`][super::ClientBuilder::timeout])
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// start from `cursor` (e.g. a [`Page::next`] saved from an earlier run) instead of from the
+    /// first page
+    pub fn resume_from(mut self, cursor: Cursor) -> Self {
+        self.endpoint.set_cursor(Some(cursor));
+        self
+    }
+
+    /// a stream of each page of results, along with the cursor for the page after it
+    pub fn pages(self) -> impl Stream<Item = Result<Page<E::Item>, RequestError>> + Send {
+        let Self {
+            client,
+            endpoint,
+            limit,
+            timeout,
+        } = self;
+        stream::try_unfold(Some((client, endpoint, limit)), move |state| async move {
+            let Some((client, mut endpoint, remaining)) = state else {
+                return Ok(None);
+            };
+            if remaining == Some(0) {
+                return Ok(None);
+            }
+            let response = client.request_with_timeout(&endpoint, timeout).await?;
+            let mut page = endpoint.to_page(response.payload);
+            let remaining = remaining.map(|remaining| {
+                if page.items.len() > remaining {
+                    page.next = page.items[..remaining]
+                        .last()
+                        .and_then(|last| endpoint.cursor_within_page(remaining, last));
+                    page.items.truncate(remaining);
+                }
+                remaining - page.items.len()
+            });
+            let state = page.next.clone().map(|cursor| {
+                endpoint.set_cursor(Some(cursor));
+                (client, endpoint, remaining)
+            });
+            Ok(Some((page, state)))
+        })
+    }
+
+    /// a stream of every item, across all the pages
+    pub fn items(self) -> impl Stream<Item = Result<E::Item, RequestError>> + Send {
+        self.pages()
+            .map_ok(|page| stream::iter(page.items).map(Ok))
+            .try_flatten()
+    }
+}
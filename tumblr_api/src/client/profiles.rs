@@ -0,0 +1,422 @@
+//! managing several sets of credentials in one program, e.g. for bots that post to blogs
+//! belonging to different apps.
+//!
+//! [`Profiles`] are loaded from a config file (toml or json) and/or environment variables, then
+//! turned into a [`Client`] per profile via [`Profiles::clients`]. the clients all share one
+//! http backend (and so one connection pool).
+//!
+//! a config file looks like
+//! ```toml
+//! [profiles.art-bot]
+//! consumer_key = "..."
+//! consumer_secret = "..."
+//! blogs = ["my-art-blog"]
+//!
+//! [profiles.news-bot]
+//! consumer_key = "..."
+//! consumer_secret = "..."
+//! blogs = ["my-news-blog", "my-other-news-blog"]
+//! ```
+//! or in json, `{"profiles": {"art-bot": {"consumer_key": "...", ...}, ...}}`.
+//!
+//! from the environment, `TUMBLR_CONSUMER_KEY`, `TUMBLR_CONSUMER_SECRET` and (optionally,
+//! comma-separated) `TUMBLR_BLOGS` make up a profile named [`DEFAULT_PROFILE`], and
+//! `TUMBLR_PROFILE_<NAME>_CONSUMER_KEY` etc. make up a profile named `<name>`, lowercased and
+//! with underscores turned into dashes - so `TUMBLR_PROFILE_ART_BOT_*` is the `art-bot` profile,
+//! matching a config file's `[profiles.art-bot]`.
+//!
+//! # Examples
+//! ```no_run
+//! use tumblr_api::client::profiles::Profiles;
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! // anything set in the environment takes precedence over the config file
+//! let profiles = Profiles::from_file("tumblr.toml")?.merge(Profiles::from_env()?);
+//! let clients = profiles.clients();
+//! if let Some(client) = clients.get("art-bot") {
+//!     client.user_info().send().await?;
+//! }
+//! if let Some(client) = clients.for_blog("my-news-blog") {
+//!     client.blog_info("my-news-blog").send().await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use serde::Deserialize;
+use veil::Redact;
+
+use super::{Client, ClientBuilder};
+use crate::{auth::Credentials, backend::HttpBackend};
+
+/// the name of the profile made from the unprefixed `TUMBLR_*` environment variables
+pub const DEFAULT_PROFILE: &str = "default";
+
+const ENV_PREFIX: &str = "TUMBLR_";
+const ENV_PROFILE_PREFIX: &str = "TUMBLR_PROFILE_";
+
+/// one set of credentials, along with the blogs it's used for
+///
+/// [`Profile`]'s [`Debug`][std::fmt::Debug] implementation is redacted (via [`veil`]), like
+/// [`Credentials`]'s is.
+#[derive(Redact, Clone, PartialEq, Eq, Deserialize)]
+pub struct Profile {
+    #[redact]
+    pub consumer_key: String,
+    #[redact]
+    pub consumer_secret: String,
+    /// the blogs this profile posts to, for looking up clients via [`ProfileClients::for_blog`]
+    #[serde(default)]
+    pub blogs: Vec<String>,
+    /// see [`ClientBuilder::api_base_url`]
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    /// see [`ClientBuilder::oauth_token_url`]
+    #[serde(default)]
+    pub oauth_token_url: Option<String>,
+}
+
+impl Profile {
+    #[must_use]
+    pub fn credentials(&self) -> Credentials {
+        Credentials::new(self.consumer_key.clone(), self.consumer_secret.clone())
+    }
+
+    /// a [`ClientBuilder`] for this profile, sending requests through `backend`
+    #[must_use]
+    pub fn client_builder<B: HttpBackend + 'static>(&self, backend: B) -> ClientBuilder {
+        let mut builder = Client::builder_with_backend(self.credentials(), backend);
+        if let Some(api_base_url) = &self.api_base_url {
+            builder = builder.api_base_url(api_base_url.as_str());
+        }
+        if let Some(oauth_token_url) = &self.oauth_token_url {
+            builder = builder.oauth_token_url(oauth_token_url.as_str());
+        }
+        builder
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProfileError {
+    #[error("couldn't read {path}: {source}")]
+    Io {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("invalid toml config: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("invalid json config: {0}")]
+    Json(#[from] serde_json::Error),
+    /// some but not all of a profile's required environment variables were set
+    #[error("profile {profile:?} is missing environment variable {variable}")]
+    MissingVariable { profile: String, variable: String },
+}
+
+/// a set of named [`Profile`]s, see the [module docs][self]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Profiles {
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    /// # Errors
+    /// returns an error if `toml` isn't a valid config
+    pub fn from_toml_str(toml: &str) -> Result<Self, ProfileError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// # Errors
+    /// returns an error if `json` isn't a valid config
+    pub fn from_json_str(json: &str) -> Result<Self, ProfileError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// load a config file, as json if its extension is `.json` and as toml otherwise
+    ///
+    /// # Errors
+    /// returns an error if the file can't be read, or isn't a valid config
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ProfileError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| ProfileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            Self::from_json_str(&contents)
+        } else {
+            Self::from_toml_str(&contents)
+        }
+    }
+
+    /// load profiles from the `TUMBLR_*` environment variables (see the [module docs][self]).
+    /// no variables being set isn't an error, it just means there are no profiles.
+    ///
+    /// # Errors
+    /// returns an error if a profile is only partially set, e.g. has a consumer key but no
+    /// consumer secret
+    pub fn from_env() -> Result<Self, ProfileError> {
+        // `std::env::vars` panics if any variable isn't unicode, even ones that have nothing to do
+        // with us, so those are skipped instead
+        Self::from_vars(std::env::vars_os().filter_map(|(key, value)| {
+            let key = key.into_string().ok()?;
+            if !key.starts_with(ENV_PREFIX) {
+                return None;
+            }
+            Some((key, value.into_string().ok()?))
+        }))
+    }
+
+    fn from_vars<I: IntoIterator<Item = (String, String)>>(vars: I) -> Result<Self, ProfileError> {
+        #[derive(Default)]
+        struct Partial {
+            consumer_key: Option<String>,
+            consumer_secret: Option<String>,
+            blogs: Option<String>,
+            api_base_url: Option<String>,
+            oauth_token_url: Option<String>,
+        }
+
+        let mut partials: BTreeMap<String, Partial> = BTreeMap::new();
+        for (key, value) in vars {
+            let (profile, setting) = if let Some(rest) = key.strip_prefix(ENV_PROFILE_PREFIX) {
+                let Some((profile, setting)) = [
+                    "CONSUMER_KEY",
+                    "CONSUMER_SECRET",
+                    "BLOGS",
+                    "API_BASE_URL",
+                    "OAUTH_TOKEN_URL",
+                ]
+                .into_iter()
+                .find_map(|setting| {
+                    let profile = rest.strip_suffix(setting)?.strip_suffix('_')?;
+                    Some((profile.to_lowercase().replace('_', "-"), setting))
+                }) else {
+                    continue;
+                };
+                (profile, setting)
+            } else if let Some(setting) = key.strip_prefix(ENV_PREFIX) {
+                (DEFAULT_PROFILE.to_string(), setting)
+            } else {
+                continue;
+            };
+            if profile.is_empty() {
+                continue;
+            }
+            let partial = partials.entry(profile).or_default();
+            let field = match setting {
+                "CONSUMER_KEY" => &mut partial.consumer_key,
+                "CONSUMER_SECRET" => &mut partial.consumer_secret,
+                "BLOGS" => &mut partial.blogs,
+                "API_BASE_URL" => &mut partial.api_base_url,
+                "OAUTH_TOKEN_URL" => &mut partial.oauth_token_url,
+                _ => continue,
+            };
+            *field = Some(value);
+        }
+
+        let mut profiles = BTreeMap::new();
+        for (name, partial) in partials {
+            let missing = |setting: &str| {
+                let variable = if name == DEFAULT_PROFILE {
+                    format!("{ENV_PREFIX}{setting}")
+                } else {
+                    format!(
+                        "{ENV_PROFILE_PREFIX}{}_{setting}",
+                        name.to_uppercase().replace('-', "_")
+                    )
+                };
+                ProfileError::MissingVariable {
+                    profile: name.clone(),
+                    variable,
+                }
+            };
+            let (consumer_key, consumer_secret) =
+                match (partial.consumer_key, partial.consumer_secret) {
+                    (Some(key), Some(secret)) => (key, secret),
+                    // e.g. just `TUMBLR_API_BASE_URL` on its own doesn't make a profile
+                    (None, None) => continue,
+                    (None, Some(_)) => return Err(missing("CONSUMER_KEY")),
+                    (Some(_), None) => return Err(missing("CONSUMER_SECRET")),
+                };
+            let blogs = partial
+                .blogs
+                .iter()
+                .flat_map(|blogs| blogs.split(','))
+                .map(str::trim)
+                .filter(|blog| !blog.is_empty())
+                .map(str::to_string)
+                .collect();
+            let profile = Profile {
+                consumer_key,
+                consumer_secret,
+                blogs,
+                api_base_url: partial.api_base_url,
+                oauth_token_url: partial.oauth_token_url,
+            };
+            profiles.insert(name, profile);
+        }
+        Ok(Self { profiles })
+    }
+
+    /// add (or replace) a profile
+    pub fn insert<S: Into<String>>(&mut self, name: S, profile: Profile) {
+        self.profiles.insert(name.into(), profile);
+    }
+
+    /// combine two sets of profiles. where both have a profile with the same name, the one
+    /// from `other` is used.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.profiles.extend(other.profiles);
+        self
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// each profile, along with its name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Profile)> {
+        self.profiles
+            .iter()
+            .map(|(name, profile)| (name.as_str(), profile))
+    }
+
+    /// a [`Client`] for each profile, all sharing one [`reqwest::Client`]
+    #[cfg(feature = "reqwest")]
+    #[must_use]
+    pub fn clients(&self) -> ProfileClients {
+        self.clients_with(reqwest::Client::new(), |_, builder| builder)
+    }
+
+    /// a [`Client`] for each profile, all sending requests through `backend`.
+    /// `configure` is called with each profile's name and [`ClientBuilder`] before it's built,
+    /// to change any other settings.
+    pub fn clients_with<B, F>(&self, backend: B, mut configure: F) -> ProfileClients
+    where
+        B: HttpBackend + 'static,
+        F: FnMut(&str, ClientBuilder) -> ClientBuilder,
+    {
+        let backend: Arc<dyn HttpBackend> = Arc::new(backend);
+        let clients = self
+            .profiles
+            .iter()
+            .map(|(name, profile)| {
+                let builder = configure(name, profile.client_builder(backend.clone()));
+                let client = builder.build();
+                (name.clone(), (client, profile.blogs.clone()))
+            })
+            .collect();
+        ProfileClients { clients }
+    }
+}
+
+/// a [`Client`] per profile, created via [`Profiles::clients`]
+#[derive(Clone)]
+pub struct ProfileClients {
+    /// each profile's client, along with its blogs
+    clients: BTreeMap<String, (Client, Vec<String>)>,
+}
+
+impl ProfileClients {
+    /// the client for the profile called `name`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Client> {
+        self.clients.get(name).map(|(client, _)| client)
+    }
+
+    /// the client for the (first, alphabetically) profile that lists `blog` among its blogs
+    #[must_use]
+    pub fn for_blog(&self, blog: &str) -> Option<&Client> {
+        self.clients
+            .values()
+            .find(|(_, blogs)| blogs.iter().any(|b| b == blog))
+            .map(|(client, _)| client)
+    }
+
+    /// each profile's client, along with the profile's name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Client)> {
+        self.clients
+            .iter()
+            .map(|(name, (client, _))| (name.as_str(), client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let profiles = Profiles::from_vars(vars(&[
+            ("TUMBLR_CONSUMER_KEY", "key"),
+            ("TUMBLR_CONSUMER_SECRET", "secret"),
+            ("TUMBLR_BLOGS", "a, b"),
+            ("TUMBLR_PROFILE_ART_BOT_CONSUMER_KEY", "art key"),
+            ("TUMBLR_PROFILE_ART_BOT_CONSUMER_SECRET", "art secret"),
+            ("TUMBLR_PROFILE_ART_BOT_API_BASE_URL", "http://localhost/v2"),
+            ("HOME", "/root"),
+        ]))
+        .unwrap();
+        let default = profiles.get(DEFAULT_PROFILE).unwrap();
+        assert_eq!(default.consumer_key, "key");
+        assert_eq!(default.blogs, ["a", "b"]);
+        let art_bot = profiles.get("art-bot").unwrap();
+        assert_eq!(art_bot.consumer_secret, "art secret");
+        assert_eq!(art_bot.api_base_url.as_deref(), Some("http://localhost/v2"));
+        assert!(art_bot.blogs.is_empty());
+        assert_eq!(profiles.iter().count(), 2);
+
+        assert!(Profiles::from_vars(vars(&[("TUMBLR_API_BASE_URL", "x")]))
+            .unwrap()
+            .get(DEFAULT_PROFILE)
+            .is_none());
+        assert!(matches!(
+            Profiles::from_vars(vars(&[("TUMBLR_PROFILE_X_CONSUMER_KEY", "key")])),
+            Err(ProfileError::MissingVariable { variable, .. })
+                if variable == "TUMBLR_PROFILE_X_CONSUMER_SECRET"
+        ));
+        assert!(matches!(
+            Profiles::from_vars(vars(&[("TUMBLR_PROFILE_ART_BOT_CONSUMER_KEY", "key")])),
+            Err(ProfileError::MissingVariable { profile, variable })
+                if profile == "art-bot" && variable == "TUMBLR_PROFILE_ART_BOT_CONSUMER_SECRET"
+        ));
+    }
+
+    #[test]
+    fn test_config_formats() {
+        let toml = Profiles::from_toml_str(
+            r#"
+            [profiles.art-bot]
+            consumer_key = "key"
+            consumer_secret = "secret"
+            blogs = ["my-art-blog"]
+            "#,
+        )
+        .unwrap();
+        let json = Profiles::from_json_str(
+            r#"{"profiles": {"art-bot": {
+                "consumer_key": "key",
+                "consumer_secret": "secret",
+                "blogs": ["my-art-blog"]
+            }}}"#,
+        )
+        .unwrap();
+        assert_eq!(toml, json);
+        assert!(Profiles::from_toml_str("[profiles.x]\nconsumer_key = 1").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! the rate limits tumblr reports via response headers.
+
+use std::time::{Duration, Instant};
+
+/// the api's request rate limits, as of some response.
+///
+/// parsed from the `X-Ratelimit-Perday-*` and `X-Ratelimit-Perhour-*` headers. either window will
+/// be `None` if the response didn't include its headers.
+///
+/// note that these are limits on the number of requests made, which are separate from the quotas
+/// on specific actions (posts, likes, etc.) described by [`api::UserLimits`][crate::api::UserLimits].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    pub per_day: Option<RateLimitWindow>,
+    pub per_hour: Option<RateLimitWindow>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitWindow {
+    /// the number of requests allowed in this window
+    pub limit: u64,
+    /// the number of requests left in this window
+    pub remaining: u64,
+    /// when this window resets (i.e. `remaining` goes back up to `limit`)
+    pub resets_at: Instant,
+}
+
+impl RateLimitWindow {
+    #[must_use]
+    pub const fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// how long until this window resets (zero if it already has)
+    #[must_use]
+    pub fn time_until_reset(&self) -> Duration {
+        self.resets_at.saturating_duration_since(Instant::now())
+    }
+
+    fn from_headers(headers: &http::header::HeaderMap, window: &str, now: Instant) -> Option<Self> {
+        let header = |name: &str| -> Option<u64> {
+            headers
+                .get(format!("x-ratelimit-{window}-{name}"))?
+                .to_str()
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+        Some(Self {
+            limit: header("limit")?,
+            remaining: header("remaining")?,
+            // tumblr reports this as a number of seconds from now. one too far in the future to
+            // represent is treated like a missing header, rather than panicking
+            resets_at: now.checked_add(Duration::from_secs(header("reset")?))?,
+        })
+    }
+}
+
+impl RateLimitInfo {
+    /// parse rate limit info out of a response's headers. returns `None` if there wasn't any.
+    #[must_use]
+    pub fn from_headers(headers: &http::header::HeaderMap) -> Option<Self> {
+        let now = Instant::now();
+        let info = Self {
+            per_day: RateLimitWindow::from_headers(headers, "perday", now),
+            per_hour: RateLimitWindow::from_headers(headers, "perhour", now),
+        };
+        (info != Self::default()).then_some(info)
+    }
+
+    /// whether either window has no requests remaining
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.windows().any(RateLimitWindow::is_exhausted)
+    }
+
+    /// how long until every exhausted window has reset, i.e. how long to wait before sending
+    /// another request. zero if no window is exhausted.
+    #[must_use]
+    pub fn time_until_available(&self) -> Duration {
+        self.windows()
+            .filter(|window| window.is_exhausted())
+            .map(RateLimitWindow::time_until_reset)
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn windows(&self) -> impl Iterator<Item = &RateLimitWindow> {
+        self.per_day.iter().chain(self.per_hour.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_headers() {
+        let mut headers = http::header::HeaderMap::new();
+        assert_eq!(RateLimitInfo::from_headers(&headers), None);
+        for (name, value) in [
+            ("X-Ratelimit-Perday-Limit", "5000"),
+            ("X-Ratelimit-Perday-Remaining", "4999"),
+            ("X-Ratelimit-Perday-Reset", "86399"),
+            ("X-Ratelimit-Perhour-Limit", "1000"),
+            ("X-Ratelimit-Perhour-Remaining", "0"),
+            ("X-Ratelimit-Perhour-Reset", "30"),
+        ] {
+            headers.insert(name, value.parse().unwrap());
+        }
+        let info = RateLimitInfo::from_headers(&headers).unwrap();
+        let per_day = info.per_day.unwrap();
+        assert_eq!((per_day.limit, per_day.remaining), (5000, 4999));
+        let per_hour = info.per_hour.unwrap();
+        assert_eq!((per_hour.limit, per_hour.remaining), (1000, 0));
+        assert!(info.is_exhausted());
+        let wait = info.time_until_available();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_reset_overflow() {
+        let mut headers = http::header::HeaderMap::new();
+        for (name, value) in [
+            ("X-Ratelimit-Perday-Limit", "5000"),
+            ("X-Ratelimit-Perday-Remaining", "0"),
+            ("X-Ratelimit-Perday-Reset", "18446744073709551615"),
+            ("X-Ratelimit-Perhour-Limit", "1000"),
+            ("X-Ratelimit-Perhour-Remaining", "999"),
+            ("X-Ratelimit-Perhour-Reset", "30"),
+        ] {
+            headers.insert(name, value.parse().unwrap());
+        }
+        let info = RateLimitInfo::from_headers(&headers).unwrap();
+        assert_eq!(info.per_day, None);
+        assert!(!info.is_exhausted());
+    }
+}
@@ -0,0 +1,218 @@
+//! automatically retrying requests that failed for (probably) transient reasons.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+/// controls if and how a [`Client`][super::Client] retries failed requests.
+///
+/// requests are retried when the api responds with a `429 Too Many Requests` or any `5xx`
+/// status, or when connecting to it fails or times out. the delay before each retry grows
+/// exponentially (with some random jitter) from [`initial_backoff`][RetryPolicy::initial_backoff]
+/// up to [`max_backoff`][RetryPolicy::max_backoff], unless the response included a `Retry-After`
+/// header, in which case that's used instead.
+///
+/// requests that aren't idempotent (e.g. creating a post) are only retried if
+/// [`retry_non_idempotent`][RetryPolicy::retry_non_idempotent] is set, since the api may have
+/// acted on the original request even though it responded with an error.
+///
+/// ```
+/// use std::time::Duration;
+/// use tumblr_api::{auth::Credentials, client::{Client, RetryPolicy}};
+/// let client = Client::builder(Credentials::new("your consumer key", "your consumer secret"))
+///     .retry_policy(
+///         RetryPolicy::new()
+///             .max_attempts(5)
+///             .initial_backoff(Duration::from_secs(1)),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// a policy making up to 3 attempts, starting with a 500ms backoff.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// a policy that never retries. this is what clients use unless configured otherwise.
+    #[must_use]
+    pub const fn none() -> Self {
+        Self::new().max_attempts(1)
+    }
+
+    /// the maximum number of times a request will be sent, including the first attempt.
+    /// (values less than 1 are treated as 1.)
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// the delay before the first retry
+    #[must_use]
+    pub const fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// the longest the exponential backoff will grow to. (doesn't limit delays requested by the
+    /// api via `Retry-After`.)
+    #[must_use]
+    pub const fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// whether to also retry non-idempotent requests, like creating a post.
+    /// this can result in duplicate posts if the api did create the post but still responded
+    /// with an error.
+    #[must_use]
+    pub const fn retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// whether a request made with `method` is allowed to be retried at all
+    pub(super) fn allows_retrying(&self, method: &http::Method) -> bool {
+        self.max_attempts > 1 && (self.retry_non_idempotent || is_idempotent(method))
+    }
+
+    /// whether another attempt is allowed after `attempts` attempts have been made
+    pub(super) const fn allows_attempt_after(&self, attempts: u32) -> bool {
+        attempts < self.max_attempts
+    }
+
+    /// the delay before the retry following attempt number `attempt` (starting from 1)
+    pub(super) fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after;
+        }
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        // "equal jitter" - somewhere between half the backoff and the full backoff
+        let half = backoff / 2;
+        half + half.mul_f64(random_fraction())
+    }
+}
+
+fn is_idempotent(method: &http::Method) -> bool {
+    matches!(
+        *method,
+        http::Method::GET
+            | http::Method::HEAD
+            | http::Method::PUT
+            | http::Method::DELETE
+            | http::Method::OPTIONS
+    )
+}
+
+/// whether a response with this status is worth retrying
+pub(super) fn is_retryable_status(status: http::StatusCode) -> bool {
+    status == http::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// whether a failed request is worth retrying
+pub(super) fn is_retryable_error(err: &crate::backend::TransportError) -> bool {
+    matches!(
+        err.kind(),
+        crate::backend::TransportErrorKind::Connect | crate::backend::TransportErrorKind::Timeout
+    )
+}
+
+/// parses a `Retry-After` header, which is either a number of seconds or an http date
+pub(super) fn retry_after(headers: &http::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(http::header::RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at =
+        time::OffsetDateTime::parse(value.trim(), &time::format_description::well_known::Rfc2822)
+            .ok()?;
+    // a date in the past means we can retry right away
+    Some(
+        SystemTime::from(at)
+            .duration_since(SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
+/// a (non-cryptographically) random number in `0.0..1.0`, without pulling in an rng crate
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    // keep 53 bits, which is all an f64's mantissa can hold
+    #[allow(clippy::cast_precision_loss)]
+    let fraction = (bits >> 11) as f64 / (1u64 << 53) as f64;
+    fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_bounds() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5));
+        for _ in 0..100 {
+            let first = policy.backoff(1, None);
+            assert!(first >= Duration::from_millis(500) && first <= Duration::from_secs(1));
+            let third = policy.backoff(3, None);
+            assert!(third >= Duration::from_secs(2) && third <= Duration::from_secs(4));
+            let capped = policy.backoff(30, None);
+            assert!(capped >= Duration::from_millis(2500) && capped <= Duration::from_secs(5));
+        }
+        assert_eq!(
+            policy.backoff(1, Some(Duration::from_secs(45))),
+            Duration::from_secs(45)
+        );
+    }
+
+    #[test]
+    fn test_retry_after_parsing() {
+        let mut headers = http::header::HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(http::header::RETRY_AFTER, "90".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(90)));
+        headers.insert(
+            http::header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_idempotency() {
+        let policy = RetryPolicy::new();
+        assert!(policy.allows_retrying(&http::Method::GET));
+        assert!(!policy.allows_retrying(&http::Method::POST));
+        assert!(policy
+            .retry_non_idempotent(true)
+            .allows_retrying(&http::Method::POST));
+        assert!(!RetryPolicy::none().allows_retrying(&http::Method::GET));
+    }
+}
@@ -0,0 +1,130 @@
+//! streaming attachments to the api as they're read, instead of holding them in memory, see
+//! [`CreatePostRequestBuilder::add_attachment_stream`][super::CreatePostRequestBuilder::add_attachment_stream].
+
+use std::{borrow::Cow, fmt};
+
+use bytes::Bytes;
+use futures_util::{stream, StreamExt};
+
+use crate::{
+    api::{
+        multipart, CreatePostEndpoint, EndpointError, PreparedBody, PreparedPart, PreparedRequest,
+        RequestBody,
+    },
+    backend::BodyStream,
+};
+
+/// a file uploaded along with a new post, read from a stream as the request is sent
+pub(super) struct StreamedAttachment {
+    pub(super) stream: BodyStream,
+    pub(super) mime_type: Box<str>,
+    pub(super) identifier: Cow<'static, str>,
+}
+
+impl fmt::Debug for StreamedAttachment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamedAttachment")
+            .field("mime_type", &self.mime_type)
+            .field("identifier", &self.identifier)
+            .finish_non_exhaustive()
+    }
+}
+
+/// a request body that's sent as it's read, along with its `Content-Type`
+pub(super) struct StreamingBody {
+    pub(super) content_type: String,
+    pub(super) stream: BodyStream,
+}
+
+impl StreamingBody {
+    /// the multipart form `endpoint` would be sent as, with each of `streamed` as another part
+    /// after its other attachments
+    pub(super) fn create_post(
+        endpoint: &CreatePostEndpoint,
+        streamed: Vec<StreamedAttachment>,
+    ) -> Result<Self, EndpointError> {
+        let boundary = multipart::random_boundary();
+        let mut segments = Vec::new();
+        for part in crate::api::multipart_parts(
+            RequestBody::json(&endpoint.request)?,
+            &endpoint.attachments,
+        )? {
+            segments.push(segment(multipart::part_headers(
+                &boundary,
+                &part.name,
+                part.file_name,
+                &part.mime_type,
+            )));
+            segments.push(segment(part.data));
+            segments.push(segment(multipart::PART_END));
+        }
+        for attachment in streamed {
+            crate::api::check_mime_type(&attachment.mime_type)?;
+            segments.push(segment(multipart::part_headers(
+                &boundary,
+                &attachment.identifier,
+                Some(multipart::ATTACHMENT_FILE_NAME),
+                &attachment.mime_type,
+            )));
+            segments.push(attachment.stream);
+            segments.push(segment(multipart::PART_END));
+        }
+        segments.push(segment(multipart::closing_delimiter(&boundary)));
+        Ok(Self {
+            content_type: multipart::content_type(&boundary),
+            stream: Box::pin(stream::iter(segments).flatten()),
+        })
+    }
+
+    /// take the body `request` was built with out, and put this one's `Content-Type` in its
+    /// place, returning the stream to send instead
+    pub(super) fn replace_body(
+        self,
+        request: &mut http::Request<Bytes>,
+    ) -> Result<BodyStream, http::Error> {
+        *request.body_mut() = Bytes::new();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::try_from(self.content_type)?,
+        );
+        Ok(self.stream)
+    }
+}
+
+/// add `streamed` to `prepared`, the description of the request `endpoint` makes, as parts after
+/// its other attachments
+pub(super) fn prepare_create_post(
+    endpoint: &CreatePostEndpoint,
+    streamed: &[StreamedAttachment],
+    prepared: &mut PreparedRequest,
+) -> Result<(), EndpointError> {
+    if streamed.is_empty() {
+        return Ok(());
+    }
+    let mut parts = Vec::with_capacity(streamed.len());
+    for attachment in streamed {
+        crate::api::check_mime_type(&attachment.mime_type)?;
+        parts.push(PreparedPart {
+            name: attachment.identifier.to_string(),
+            mime_type: attachment.mime_type.to_string(),
+            // it isn't known until the stream's been read
+            len: None,
+        });
+    }
+    match &mut prepared.body {
+        Some(PreparedBody::Multipart { parts: other, .. }) => other.extend(parts),
+        body => {
+            *body = Some(PreparedBody::Multipart {
+                json: serde_json::to_value(&endpoint.request)
+                    .map_err(EndpointError::Serializing)?,
+                parts,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// a stream of just `data`
+fn segment(data: impl Into<Bytes>) -> BodyStream {
+    Box::pin(stream::iter([Ok(data.into())]))
+}
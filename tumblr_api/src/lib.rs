@@ -36,9 +36,6 @@
 //! # ));
 //! // load the image that we'll be attaching to the post.
 //! let my_image = std::fs::read("path/to/my_image.jpg")?;
-//! // (currently, you need to manually create the reqwest::Body to pass in. that'll probably
-//! //  change in a future version.)
-//! let my_image = reqwest::Body::from(my_image);
 //! client
 //!     .create_post(
 //!         "blog-name",
@@ -77,6 +74,16 @@
 //! This library is split into multiple modules - `client`, `api`, `npf`, and `auth` - and each has a feature flag of the same name that controls whether it's enabled.
 //! They'll all be enabled by default, but if you only need certain features (e.g. just npf parsing) you can enable just those instead.
 //!
+//! The optional `blocking` feature adds `client::blocking`, a synchronous version of the client for code that isn't async.
+//!
+//! With the optional `tracing` feature, each request made by the client (and each time a token is fetched) is wrapped in a [`tracing`](https://docs.rs/tracing) span, recording things like the endpoint, status, latency, and number of retries. Credentials and tokens are never recorded.
+//!
+//! With the optional `profiles` feature, `client::profiles` can load several named sets of credentials from a toml/json config file or `TUMBLR_*` environment variables, and create a client for each that all share one connection pool.
+//!
+//! There's also an optional `mock` module/feature (not enabled by default), which provides a local stand-in for the Tumblr API to test your code against.
+//!
+//! Requests are sent through the `backend` module's `HttpBackend` trait (available with `auth` or `client`). The `reqwest` feature (on by default) implements it for `reqwest::Client` and uses that unless told otherwise; turn it off if you're bringing your own backend.
+//!
 //! # Major Planned/Unimplemented Features
 //! - implement remaining api endpoints (currently it's just post creation plus a couple others)
 
@@ -116,7 +123,11 @@
 pub mod api;
 #[cfg(feature = "auth")]
 pub mod auth;
+#[cfg(feature = "auth")]
+pub mod backend;
 #[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "mock")]
+pub mod mock;
 #[cfg(feature = "npf")]
 pub mod npf;
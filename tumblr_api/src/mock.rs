@@ -0,0 +1,981 @@
+//! an in-process stand-in for the tumblr api, for testing code that uses [`client`][crate::client].
+//!
+//! [`MockServer`] serves the OAuth token endpoint plus the post (create/fetch/edit/delete/list),
+//! draft list, blog info, like, user info, and user limits endpoints on a local port, keeping
+//! everything in memory. Faults (rate limiting, server errors, api errors with specific subcodes,
+//! slow responses) can be queued up to test how your code handles them.
+//!
+//! # Examples
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! use tumblr_api::{mock::{Fault, MockServer}, npf};
+//! let server = MockServer::start().await?;
+//! let client = server.client();
+//! client
+//!     .create_post(
+//!         MockServer::BLOG_NAME,
+//!         vec![npf::ContentBlockText::builder("hello world").build()],
+//!     )
+//!     .send()
+//!     .await?;
+//! assert_eq!(server.posts(MockServer::BLOG_NAME).len(), 1);
+//!
+//! // the next request will fail with a 429
+//! server.inject_fault(Fault::RateLimited);
+//! assert!(client.user_info().send().await.is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    convert::Infallible,
+    hash::{Hash, Hasher},
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Server, StatusCode,
+};
+use serde_json::{json, Value};
+
+use crate::{api::ApiErrorCode, auth::Credentials, client::Client};
+
+/// a failure the [`MockServer`] will respond with instead of handling a request normally.
+///
+/// queued via [`MockServer::inject_fault`]. token requests are never affected by faults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// respond with a `429 Limit Exceeded` api error
+    RateLimited,
+    /// respond with a non-json (html) body and the given status, like tumblr's edge servers do
+    /// when something goes wrong upstream
+    ServerError(u16),
+    /// respond with an api error with the given status and error subcode
+    ApiError {
+        status: u16,
+        code: i32,
+        title: String,
+        detail: String,
+    },
+    /// respond with exactly this body (as json) and status, e.g. to imitate a response the
+    /// client can't parse
+    Raw { status: u16, body: String },
+    /// handle the request normally, but wait this long before responding, e.g. to test timeouts
+    Delay(Duration),
+}
+
+/// a post stored by the [`MockServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockPost {
+    pub id: i64,
+    pub blog_name: String,
+    /// the post's NPF content, exactly as it was submitted
+    pub content: Value,
+    pub tags: Vec<String>,
+    /// the post's state, as the api reports it (`published`, `queued`, `draft`, or `private`)
+    pub state: String,
+    pub source_url: Option<String>,
+    /// identifiers of the multipart attachments the post was created with
+    pub attachments: Vec<String>,
+    pub timestamp: i64,
+}
+
+/// the quota the [`MockServer`] reports for each of the entries in [`LimitsResponse`][crate::api::LimitsResponse].
+const DEFAULT_LIMITS: &[(&str, &str, i64)] = &[
+    ("blogs", "Secondary blogs created per day", 10),
+    ("follows", "Blogs followed per day", 200),
+    ("likes", "Posts liked per day", 1000),
+    ("photos", "Photos uploaded per day", 150),
+    ("posts", "Posts created per day", 250),
+    ("video_seconds", "Seconds of video uploaded per day", 600),
+    ("videos", "Videos uploaded per day", 10),
+];
+
+/// how long real tokens are valid for, at the time of writing
+const DEFAULT_TOKEN_LIFETIME_SECS: u64 = 2520;
+
+struct State {
+    issued_tokens: HashMap<String, Instant>,
+    token_lifetime: Duration,
+    blogs: HashSet<String>,
+    posts: HashMap<String, Vec<MockPost>>,
+    liked_posts: Vec<i64>,
+    limits: HashMap<&'static str, i64>,
+    faults: VecDeque<Fault>,
+    next_post_id: i64,
+    next_token_id: u64,
+    request_count: usize,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            issued_tokens: HashMap::new(),
+            token_lifetime: Duration::from_secs(DEFAULT_TOKEN_LIFETIME_SECS),
+            blogs: [MockServer::BLOG_NAME.to_string()].into(),
+            posts: HashMap::new(),
+            liked_posts: Vec::new(),
+            limits: DEFAULT_LIMITS
+                .iter()
+                .map(|(name, _, limit)| (*name, *limit))
+                .collect(),
+            faults: VecDeque::new(),
+            next_post_id: 700_000_000_000_000_000,
+            next_token_id: 1,
+            request_count: 0,
+        }
+    }
+}
+
+/// a local http server that behaves like (a small part of) the tumblr api.
+///
+/// the server shuts down when this is dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// the consumer key the server accepts
+    pub const CONSUMER_KEY: &'static str = "mock-consumer-key";
+    /// the consumer secret the server accepts
+    pub const CONSUMER_SECRET: &'static str = "mock-consumer-secret";
+    /// the name of the blog the mock user owns by default
+    pub const BLOG_NAME: &'static str = "mock-blog";
+
+    /// start a server on a random local port.
+    ///
+    /// must be called from within a tokio runtime.
+    ///
+    /// # Errors
+    /// returns an error if binding the local port fails
+    // (this doesn't await anything, but is async since it needs to be called from a runtime)
+    #[allow(clippy::unused_async)]
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::new()));
+        let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let service_state = Arc::clone(&state);
+        let make_service = make_service_fn(move |_conn| {
+            let state = Arc::clone(&service_state);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    handle(Arc::clone(&state), request)
+                }))
+            }
+        });
+        let server = Server::from_tcp(listener)
+            .map_err(std::io::Error::other)?
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            });
+        tokio::spawn(server);
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// the api base url to point a client at, see [`ClientBuilder::api_base_url`][crate::client::ClientBuilder::api_base_url]
+    #[must_use]
+    pub fn api_base_url(&self) -> String {
+        format!("http://{}/v2", self.addr)
+    }
+
+    /// the token url to point a client at, see [`ClientBuilder::oauth_token_url`][crate::client::ClientBuilder::oauth_token_url]
+    #[must_use]
+    pub fn oauth_token_url(&self) -> String {
+        format!("http://{}/v2/oauth2/token", self.addr)
+    }
+
+    /// credentials the server will accept
+    #[must_use]
+    pub fn credentials() -> Credentials {
+        Credentials::new(Self::CONSUMER_KEY, Self::CONSUMER_SECRET)
+    }
+
+    /// a [`Client`] that talks to this server, using credentials it will accept
+    #[must_use]
+    pub fn client(&self) -> Client {
+        Client::builder(Self::credentials())
+            .api_base_url(self.api_base_url())
+            .oauth_token_url(self.oauth_token_url())
+            .build()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+
+    /// queue up a fault. each queued fault is used (in order) to respond to one request.
+    pub fn inject_fault(&self, fault: Fault) {
+        self.state().faults.push_back(fault);
+    }
+
+    /// add a blog that the mock user can post to
+    pub fn add_blog<S: Into<String>>(&self, blog_name: S) {
+        self.state().blogs.insert(blog_name.into());
+    }
+
+    /// all posts currently on `blog_name`, oldest first
+    #[must_use]
+    pub fn posts(&self, blog_name: &str) -> Vec<MockPost> {
+        self.state()
+            .posts
+            .get(blog_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// the ids of the posts the mock user has liked, in the order they were liked
+    #[must_use]
+    pub fn liked_posts(&self) -> Vec<i64> {
+        self.state().liked_posts.clone()
+    }
+
+    /// set how many of a quota (e.g. `"posts"`, see [`UserLimits`][crate::api::UserLimits]) remain
+    pub fn set_remaining(&self, limit_name: &'static str, remaining: i64) {
+        self.state().limits.insert(limit_name, remaining);
+    }
+
+    /// set how long newly issued tokens are valid for
+    pub fn set_token_lifetime(&self, lifetime: Duration) {
+        self.state().token_lifetime = lifetime;
+    }
+
+    /// invalidate every token issued so far, as if tumblr revoked them early
+    pub fn revoke_tokens(&self) {
+        self.state().issued_tokens.clear();
+    }
+
+    /// the number of tokens that have been issued
+    #[must_use]
+    pub fn tokens_issued(&self) -> u64 {
+        self.state().next_token_id - 1
+    }
+
+    /// the number of (non-token) api requests received
+    #[must_use]
+    pub fn request_count(&self) -> usize {
+        self.state().request_count
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    // none of the handlers can panic while holding the lock, but there's no reason to make that
+    // everyone else's problem if one does
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+type HyperResponse = hyper::Response<Body>;
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Body>,
+) -> Result<HyperResponse, Infallible> {
+    let (parts, body) = request.into_parts();
+    let Ok(body) = hyper::body::to_bytes(body).await else {
+        return Ok(api_error(400, "Bad Request", 0, "unreadable request body"));
+    };
+    let (response, delay) = {
+        let mut state = lock(&state);
+        if parts.method == Method::POST && parts.uri.path() == "/v2/oauth2/token" {
+            return Ok(token(&mut state, &body));
+        }
+
+        state.request_count += 1;
+        let fault = state.faults.pop_front();
+        let mut response = match fault.as_ref().and_then(fault_response) {
+            Some(response) => response,
+            None => respond(&mut state, &parts, &body),
+        };
+        add_rate_limit_headers(&state, response.headers_mut());
+        let delay = match fault {
+            Some(Fault::Delay(delay)) => Some(delay),
+            _ => None,
+        };
+        (response, delay)
+    };
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+    Ok(response)
+}
+
+fn respond(state: &mut State, parts: &hyper::http::request::Parts, body: &[u8]) -> HyperResponse {
+    if !is_authorized(state, &parts.headers) {
+        return api_error(401, "Unauthorized", 0, "invalid or expired token");
+    }
+    let path = parts.uri.path().trim_start_matches("/v2/");
+    let segments: Vec<&str> = path.split('/').collect();
+    match (&parts.method, segments.as_slice()) {
+        (&Method::GET, ["user", "info"]) => user_info(state),
+        (&Method::GET, ["user", "limits"]) => user_limits(state),
+        (&Method::POST, ["user", "like"]) => like_post(state, &parts.headers, body),
+        (&Method::POST, ["blog", blog, "posts"]) => create_post(state, blog, &parts.headers, body),
+        (&Method::GET, ["blog", blog, "posts"]) => list_posts(state, blog, &query(parts)),
+        (&Method::GET, ["blog", blog, "posts", "draft"]) => list_drafts(state, blog, &query(parts)),
+        (&Method::GET, ["blog", blog, "info"]) => blog_info(state, blog, &parts.headers),
+        (&Method::GET, ["blog", blog, "avatar"]) => avatar(state, blog, "64"),
+        (&Method::GET, ["blog", blog, "avatar", size]) => avatar(state, blog, size),
+        (&Method::GET, ["blog", blog, "posts", id]) => fetch_post(state, blog, id, &parts.headers),
+        (&Method::PUT, ["blog", blog, "posts", id]) => {
+            edit_post(state, blog, id, &parts.headers, body)
+        }
+        (&Method::POST, ["blog", blog, "post", "delete"]) => {
+            delete_post(state, blog, &parts.headers, body)
+        }
+        _ => api_error(404, "Not Found", 0, "no such endpoint"),
+    }
+}
+
+/// the request rate limits the server reports, as `(window, limit, window length in seconds)`
+const RATE_LIMITS: &[(&str, usize, u64)] = &[("perday", 5000, 86400), ("perhour", 1000, 3600)];
+
+fn add_rate_limit_headers(state: &State, headers: &mut header::HeaderMap) {
+    for (window, limit, reset) in RATE_LIMITS {
+        let remaining = limit.saturating_sub(state.request_count);
+        for (name, value) in [
+            ("limit", limit.to_string()),
+            ("remaining", remaining.to_string()),
+            ("reset", reset.to_string()),
+        ] {
+            if let (Ok(name), Ok(value)) = (
+                header::HeaderName::from_bytes(format!("x-ratelimit-{window}-{name}").as_bytes()),
+                header::HeaderValue::from_str(&value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+fn json_response(status: u16, value: &Value) -> HyperResponse {
+    hyper::Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(value.to_string()))
+        .unwrap_or_default()
+}
+
+fn success(status: u16, msg: &str, response: &Value) -> HyperResponse {
+    json_response(
+        status,
+        &json!({ "meta": { "status": status, "msg": msg }, "response": response }),
+    )
+}
+
+/// a `200 OK` response with an `ETag`, or a `304 Not Modified` if the request's `If-None-Match`
+/// says the client already has it
+fn conditional_success(request_headers: &header::HeaderMap, response: &Value) -> HyperResponse {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    response.to_string().hash(&mut hasher);
+    let etag = format!("\"{:x}\"", hasher.finish());
+    let mut response = if request_headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| value.as_bytes() == etag.as_bytes())
+    {
+        hyper::Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap_or_default()
+    } else {
+        success(200, "OK", response)
+    };
+    if let Ok(etag) = header::HeaderValue::from_str(&etag) {
+        response.headers_mut().insert(header::ETAG, etag);
+    }
+    response
+}
+
+fn api_error(status: u16, title: &str, code: i32, detail: &str) -> HyperResponse {
+    json_response(
+        status,
+        &json!({
+            "meta": { "status": status, "msg": title },
+            "response": [],
+            "errors": [{ "title": title, "code": code, "detail": detail }],
+        }),
+    )
+}
+
+/// the response `fault` replaces the normal response with, if it does
+fn fault_response(fault: &Fault) -> Option<HyperResponse> {
+    let response = match fault {
+        Fault::RateLimited => {
+            let mut response = api_error(429, "Limit Exceeded", 0, "rate limit exceeded");
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from_static("1"));
+            response
+        }
+        Fault::ServerError(status) => {
+            let status = StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_GATEWAY);
+            hyper::Response::builder()
+                .status(status)
+                .header(header::CONTENT_TYPE, "text/html")
+                .body(Body::from(format!(
+                    "<html><body><h1>{status}</h1></body></html>"
+                )))
+                .unwrap_or_default()
+        }
+        Fault::ApiError {
+            status,
+            code,
+            title,
+            detail,
+        } => api_error(*status, title, *code, detail),
+        Fault::Raw { status, body } => hyper::Response::builder()
+            .status(*status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.clone()))
+            .unwrap_or_default(),
+        Fault::Delay(_) => return None,
+    };
+    Some(response)
+}
+
+fn token(state: &mut State, body: &[u8]) -> HyperResponse {
+    let form: HashMap<_, _> = form_urlencoded::parse(body).into_owned().collect();
+    let field = |name: &str| form.get(name).map(String::as_str);
+    if field("grant_type") != Some("client_credentials") {
+        return json_response(400, &json!({ "error": "unsupported_grant_type" }));
+    }
+    if field("client_id") != Some(MockServer::CONSUMER_KEY)
+        || field("client_secret") != Some(MockServer::CONSUMER_SECRET)
+    {
+        return json_response(
+            401,
+            &json!({ "error": "invalid_client", "error_description": "unknown consumer key or secret" }),
+        );
+    }
+    let token = format!("mock-token-{}", state.next_token_id);
+    state.next_token_id += 1;
+    state
+        .issued_tokens
+        .insert(token.clone(), Instant::now() + state.token_lifetime);
+    json_response(
+        200,
+        &json!({
+            "access_token": token,
+            "expires_in": state.token_lifetime.as_secs(),
+            "token_type": "bearer",
+            "scope": field("scope").unwrap_or_default(),
+        }),
+    )
+}
+
+fn is_authorized(state: &State, headers: &header::HeaderMap) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| state.issued_tokens.get(token))
+        .is_some_and(|expires_at| Instant::now() < *expires_at)
+}
+
+fn user_info(state: &State) -> HyperResponse {
+    let mut blogs: Vec<&String> = state.blogs.iter().collect();
+    blogs.sort();
+    let blogs: Vec<Value> = blogs
+        .into_iter()
+        .map(|name| {
+            json!({
+                "name": name,
+                "url": format!("https://{name}.tumblr.com/"),
+                "title": name,
+                "primary": name == MockServer::BLOG_NAME,
+                "followers": 0,
+                "tweet": "N",
+                "type": "public",
+            })
+        })
+        .collect();
+    success(
+        200,
+        "OK",
+        &json!({
+            "user": {
+                "following": 0,
+                "default_post_format": "html",
+                "name": MockServer::BLOG_NAME,
+                "likes": 0,
+                "blogs": blogs,
+            }
+        }),
+    )
+}
+
+fn blog_info(state: &State, blog: &str, request_headers: &header::HeaderMap) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let posts = state.posts.get(blog).map_or(&[][..], Vec::as_slice);
+    conditional_success(
+        request_headers,
+        &json!({
+            "blog": {
+                "name": blog,
+                "title": blog,
+                "description": "",
+                "url": format!("https://{blog}.tumblr.com/"),
+                "uuid": format!("t:mock-{blog}"),
+                "updated": posts.last().map_or(0, |post| post.timestamp),
+                "posts": posts.len(),
+                "ask": false,
+            }
+        }),
+    )
+}
+
+/// a `301 Moved Permanently` to the avatar, with its url in the body too, like the real api
+fn avatar(state: &State, blog: &str, size: &str) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let url = format!("https://assets.tumblr.com/mock/avatar_{blog}_{size}.png");
+    let mut response = success(301, "Moved Permanently", &json!({ "avatar_url": url }));
+    if let Ok(location) = header::HeaderValue::from_str(&url) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    response
+}
+
+fn user_limits(state: &State) -> HyperResponse {
+    let reset_at = unix_now() + 24 * 60 * 60;
+    let limits: serde_json::Map<String, Value> = DEFAULT_LIMITS
+        .iter()
+        .map(|(name, description, limit)| {
+            let remaining = state.limits.get(name).copied().unwrap_or(*limit);
+            (
+                (*name).to_string(),
+                json!({
+                    "description": description,
+                    "limit": limit,
+                    "remaining": remaining,
+                    "reset_at": reset_at,
+                }),
+            )
+        })
+        .collect();
+    success(200, "OK", &json!({ "user": limits }))
+}
+
+/// pulls the json out of a request body, which is either the whole body or (for requests with
+/// attachments) the multipart part named `json`. also returns the names of any other parts.
+fn request_json(headers: &header::HeaderMap, body: &[u8]) -> Option<(Value, Vec<String>)> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if let Some(boundary) = content_type
+        .strip_prefix("multipart/form-data")
+        .and_then(|rest| rest.split("boundary=").nth(1))
+    {
+        let parts = multipart_parts(body, boundary.trim_matches('"'));
+        let mut json = None;
+        let mut attachments = Vec::new();
+        for (name, content) in parts {
+            if name == "json" {
+                json = serde_json::from_slice(content).ok();
+            } else {
+                attachments.push(name);
+            }
+        }
+        Some((json?, attachments))
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        let form: serde_json::Map<String, Value> = form_urlencoded::parse(body)
+            .into_owned()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect();
+        Some((Value::Object(form), Vec::new()))
+    } else {
+        Some((serde_json::from_slice(body).ok()?, Vec::new()))
+    }
+}
+
+/// a deliberately minimal multipart/form-data parser, returning `(name, content)` for each part
+fn multipart_parts<'a>(body: &'a [u8], boundary: &str) -> Vec<(String, &'a [u8])> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut rest = body;
+    while let Some(start) = find(rest, delimiter.as_bytes()) {
+        rest = &rest[start + delimiter.len()..];
+        if rest.starts_with(b"--") {
+            break;
+        }
+        let Some(headers_end) = find(rest, b"\r\n\r\n") else {
+            break;
+        };
+        let part_headers = String::from_utf8_lossy(&rest[..headers_end]);
+        let content_start = headers_end + 4;
+        let content_end = find(&rest[content_start..], delimiter.as_bytes())
+            .map_or(rest.len(), |end| content_start + end);
+        let content = rest[content_start..content_end]
+            .strip_suffix(b"\r\n")
+            .unwrap_or(&rest[content_start..content_end]);
+        if let Some(name) = part_headers
+            .split("name=\"")
+            .nth(1)
+            .and_then(|name| name.split('"').next())
+        {
+            parts.push((name.to_string(), content));
+        }
+        rest = &rest[content_end..];
+    }
+    parts
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| {
+            i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+        })
+}
+
+fn blog_not_found() -> HyperResponse {
+    api_error(
+        404,
+        "Not Found",
+        ApiErrorCode::BlogNotFound.code(),
+        "blog not found",
+    )
+}
+
+fn post_state(requested: Option<&str>) -> &'static str {
+    match requested {
+        Some("queue") => "queued",
+        // submissions end up in the blog's inbox, which the mock doesn't model, so they're
+        // treated like drafts
+        Some("draft" | "unapproved") => "draft",
+        Some("private") => "private",
+        _ => "published",
+    }
+}
+
+fn tags_of(request: &Value) -> Vec<String> {
+    request
+        .get("tags")
+        .and_then(Value::as_str)
+        .map(|tags| {
+            tags.split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn create_post(
+    state: &mut State,
+    blog: &str,
+    headers: &header::HeaderMap,
+    body: &[u8],
+) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let Some((request, attachments)) = request_json(headers, body) else {
+        return api_error(400, "Bad Request", 8001, "request body is not valid json");
+    };
+    let Some(content) = request.get("content").filter(|c| c.is_array()) else {
+        return api_error(400, "Bad Request", 8001, "content is required");
+    };
+    let remaining = state.limits.entry("posts").or_insert(0);
+    if *remaining <= 0 {
+        return api_error(
+            400,
+            "Bad Request",
+            ApiErrorCode::PostLimitExceeded.code(),
+            "daily post limit exceeded",
+        );
+    }
+    *remaining -= 1;
+    let id = state.next_post_id;
+    state.next_post_id += 1;
+    let post = MockPost {
+        id,
+        blog_name: blog.to_string(),
+        content: content.clone(),
+        tags: tags_of(&request),
+        state: post_state(request.get("state").and_then(Value::as_str)).to_string(),
+        source_url: request
+            .get("source_url")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        attachments,
+        timestamp: unix_now(),
+    };
+    let response = json!({
+        "id": id.to_string(),
+        "state": post.state,
+        "display_text": "Posted to mock",
+    });
+    state.posts.entry(blog.to_string()).or_default().push(post);
+    success(201, "Created", &response)
+}
+
+fn query(parts: &hyper::http::request::Parts) -> HashMap<String, String> {
+    form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// how many posts list endpoints return per page, unless asked for fewer
+const MAX_PAGE_SIZE: usize = 20;
+
+fn page_size(query: &HashMap<String, String>) -> usize {
+    query
+        .get("limit")
+        .and_then(|limit| limit.parse().ok())
+        .map_or(MAX_PAGE_SIZE, |limit: usize| limit.clamp(1, MAX_PAGE_SIZE))
+}
+
+/// a blog's posts in a given state, newest first
+fn posts_in_state<'a>(state: &'a State, blog: &str, post_state: &str) -> Vec<&'a MockPost> {
+    state
+        .posts
+        .get(blog)
+        .into_iter()
+        .flatten()
+        .rev()
+        .filter(|post| post.state == post_state)
+        .collect()
+}
+
+/// published posts, paginated by offset with `_links`, like the real endpoint
+fn list_posts(state: &State, blog: &str, query: &HashMap<String, String>) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let posts = posts_in_state(state, blog, "published");
+    let limit = page_size(query);
+    let offset: usize = query
+        .get("offset")
+        .and_then(|offset| offset.parse().ok())
+        .unwrap_or(0);
+    let page: Vec<Value> = posts
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .map(|post| npf_post_json(post))
+        .collect();
+    let mut response = json!({ "posts": page, "total_posts": posts.len() });
+    if offset + limit < posts.len() {
+        response["_links"] = json!({
+            "next": {
+                "href": format!("/v2/blog/{blog}/posts?offset={}", offset + limit),
+                "method": "GET",
+                "query_params": { "offset": (offset + limit).to_string(), "limit": limit.to_string() },
+            }
+        });
+    }
+    success(200, "OK", &response)
+}
+
+/// draft posts, paginated by `before_id` (without `_links`)
+fn list_drafts(state: &State, blog: &str, query: &HashMap<String, String>) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let before_id: Option<i64> = query.get("before_id").and_then(|id| id.parse().ok());
+    let page: Vec<Value> = posts_in_state(state, blog, "draft")
+        .into_iter()
+        .filter(|post| before_id.is_none_or(|before_id| post.id < before_id))
+        .take(page_size(query))
+        .map(npf_post_json)
+        .collect();
+    success(200, "OK", &json!({ "posts": page }))
+}
+
+fn find_post<'a>(state: &'a mut State, blog: &str, id: &str) -> Option<&'a mut MockPost> {
+    let id: i64 = id.parse().ok()?;
+    state
+        .posts
+        .get_mut(blog)?
+        .iter_mut()
+        .find(|post| post.id == id)
+}
+
+fn fetch_post(
+    state: &State,
+    blog: &str,
+    id: &str,
+    request_headers: &header::HeaderMap,
+) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let post = id
+        .parse::<i64>()
+        .ok()
+        .and_then(|id| state.posts.get(blog)?.iter().find(|post| post.id == id));
+    match post {
+        Some(post) => conditional_success(request_headers, &npf_post_json(post)),
+        None => api_error(404, "Not Found", 0, "post not found"),
+    }
+}
+
+fn edit_post(
+    state: &mut State,
+    blog: &str,
+    id: &str,
+    headers: &header::HeaderMap,
+    body: &[u8],
+) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let Some((request, _)) = request_json(headers, body) else {
+        return api_error(400, "Bad Request", 8001, "request body is not valid json");
+    };
+    let Some(post) = find_post(state, blog, id) else {
+        return api_error(404, "Not Found", 0, "post not found");
+    };
+    // like the real api, an edit replaces the post's content and tags with whatever's sent,
+    // rather than only changing the parts that are
+    post.content = request
+        .get("content")
+        .filter(|c| c.is_array())
+        .cloned()
+        .unwrap_or_else(|| json!([]));
+    post.tags = tags_of(&request);
+    if let Some(requested) = request.get("state").and_then(Value::as_str) {
+        post.state = post_state(Some(requested)).to_string();
+    }
+    success(200, "OK", &json!({ "id": post.id.to_string() }))
+}
+
+fn like_post(state: &mut State, headers: &header::HeaderMap, body: &[u8]) -> HyperResponse {
+    let Some((request, _)) = request_json(headers, body) else {
+        return api_error(400, "Bad Request", 0, "unreadable request body");
+    };
+    let id = request
+        .get("id")
+        .and_then(Value::as_str)
+        .and_then(|id| id.parse::<i64>().ok());
+    let reblog_key = request.get("reblog_key").and_then(Value::as_str);
+    let (Some(id), Some(reblog_key)) = (id, reblog_key) else {
+        return api_error(400, "Bad Request", 0, "id and reblog_key are required");
+    };
+    let exists = state.posts.values().flatten().any(|post| post.id == id);
+    if !exists || reblog_key != format!("mock-reblog-key-{id}") {
+        return api_error(404, "Not Found", 0, "post not found");
+    }
+    let remaining = state.limits.entry("likes").or_insert(0);
+    if *remaining <= 0 {
+        return api_error(400, "Bad Request", 0, "daily like limit exceeded");
+    }
+    *remaining -= 1;
+    state.liked_posts.push(id);
+    success(200, "OK", &json!([]))
+}
+
+fn delete_post(
+    state: &mut State,
+    blog: &str,
+    headers: &header::HeaderMap,
+    body: &[u8],
+) -> HyperResponse {
+    if !state.blogs.contains(blog) {
+        return blog_not_found();
+    }
+    let id = request_json(headers, body).and_then(|(request, _)| match request.get("id")? {
+        Value::String(id) => id.parse::<i64>().ok(),
+        id => id.as_i64(),
+    });
+    let Some(id) = id else {
+        return api_error(400, "Bad Request", 0, "id is required");
+    };
+    let Some(posts) = state.posts.get_mut(blog) else {
+        return api_error(404, "Not Found", 0, "post not found");
+    };
+    let count_before = posts.len();
+    posts.retain(|post| post.id != id);
+    if posts.len() == count_before {
+        return api_error(404, "Not Found", 0, "post not found");
+    }
+    success(200, "OK", &json!({ "id": id.to_string() }))
+}
+
+/// renders a stored post the way the api would when fetching it in NPF
+fn npf_post_json(post: &MockPost) -> Value {
+    let blog_url = format!("https://{}.tumblr.com/", post.blog_name);
+    json!({
+        "object_type": "post",
+        "type": "blocks",
+        "id": post.id,
+        "id_string": post.id.to_string(),
+        "genesis_post_id": post.id.to_string(),
+        "blog_name": post.blog_name,
+        "post_url": format!("{blog_url}post/{}", post.id),
+        "slug": "",
+        "short_url": format!("https://tmblr.co/mock{}", post.id),
+        "timestamp": post.timestamp,
+        "date": "",
+        "summary": "",
+        "reblog_key": format!("mock-reblog-key-{}", post.id),
+        "tags": post.tags,
+        "liked": false,
+        "state": post.state,
+        "is_blocks_post_format": true,
+        "original_type": "regular",
+        "blog": {
+            "name": post.blog_name,
+            "title": post.blog_name,
+            "description": "",
+            "url": blog_url,
+            "uuid": format!("t:mock-{}", post.blog_name),
+            "updated": post.timestamp,
+            "tumblrmart_accessories": {},
+            "can_show_badges": false,
+        },
+        "is_blazed": false,
+        "is_blaze_pending": false,
+        "can_ignite": false,
+        "can_blaze": false,
+        "should_open_in_legacy": false,
+        "recommended_source": null,
+        "recommended_color": null,
+        "followed": false,
+        "note_count": 0,
+        "content": post.content,
+        "layout": [],
+        "trail": [],
+        "can_like": true,
+        "interactability_reblog": "everyone",
+        "can_reblog": true,
+        "can_send_in_message": true,
+        "can_reply": true,
+        "display_avatar": true,
+    })
+}
@@ -26,11 +26,11 @@ where
     #[serde(untagged)]
     enum Foo {
         EmptyTuple([i32; 0]),
-        SingleAttributionValue(Attribution),
+        SingleAttributionValue(Box<Attribution>),
     }
     match Option::<Foo>::deserialize(deserializer)? {
         None | Some(Foo::EmptyTuple(_)) => Ok(None),
-        Some(Foo::SingleAttributionValue(v)) => Ok(Some(v)),
+        Some(Foo::SingleAttributionValue(v)) => Ok(Some(*v)),
     }
 }
 
@@ -120,7 +120,7 @@ pub struct ContentBlockText {
 #[derive(Serialize, Deserialize, Builder, Debug, PartialEq, Eq)]
 #[builder(builder_class = ContentBlockImageBuilder, build_fn(into))]
 pub struct ContentBlockImage {
-    /// "An array of [MediaObject]s which represent different available sizes of this image asset."
+    /// "An array of [`MediaObject`]s which represent different available sizes of this image asset."
     #[builder(set(ctor))]
     pub media: Vec<MediaObject>,
     /// "Colors used in the image."
@@ -506,7 +506,7 @@ pub struct EmbedIframe {
     #[serde(skip_serializing_if = "Option::is_none")]
     width: Option<i32>,
     /// "The height of the video iframe"
-    /// #[serde(skip_serializing_if = "Option::is_none")]
+    // #[serde(skip_serializing_if = "Option::is_none")]
     height: Option<i32>,
 }
 
@@ -0,0 +1,1217 @@
+use tumblr_api::{
+    api::ApiErrorCode,
+    client::{CreatePostState, RequestError},
+    mock::{Fault, MockServer},
+    npf,
+};
+
+#[tokio::test]
+async fn create_post() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    let response = client
+        .create_post(
+            MockServer::BLOG_NAME,
+            vec![npf::ContentBlockText::builder("hello world").build()],
+        )
+        .tags("tag_1,tag_2")
+        .initial_state(CreatePostState::Draft)
+        .add_attachment(vec![1, 2, 3], "image/png", "my-image")
+        .send_with_meta()
+        .await
+        .unwrap();
+    assert_eq!(response.status, 201);
+    assert_eq!(response.msg, "Created");
+    assert_eq!(response.headers["content-type"], "application/json");
+    let response = response.payload;
+    let posts = server.posts(MockServer::BLOG_NAME);
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].id.to_string(), response.id);
+    assert_eq!(posts[0].tags, ["tag_1", "tag_2"]);
+    assert_eq!(posts[0].state, "draft");
+    assert_eq!(posts[0].attachments, ["my-image"]);
+    assert_eq!(
+        posts[0].content,
+        serde_json::json!([{"type": "text", "text": "hello world"}])
+    );
+}
+
+#[tokio::test]
+async fn create_post_with_streamed_attachment() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    let chunks = [&b"first "[..], &b"second"[..]]
+        .map(|chunk| Ok::<_, std::io::Error>(bytes::Bytes::from_static(chunk)));
+    client
+        .create_post(
+            MockServer::BLOG_NAME,
+            vec![npf::ContentBlockText::builder("hello world").build()],
+        )
+        .add_attachment(vec![1, 2, 3], "image/png", "in-memory")
+        .add_attachment_stream(futures_util::stream::iter(chunks), "video/mp4", "streamed")
+        .send()
+        .await
+        .unwrap();
+    let posts = server.posts(MockServer::BLOG_NAME);
+    assert_eq!(posts[0].attachments, ["in-memory", "streamed"]);
+
+    // an error reading the stream fails the request, without retrying it
+    let chunks = [Err(std::io::Error::other("disk on fire"))];
+    let err = client
+        .create_post(MockServer::BLOG_NAME, Vec::new())
+        .add_attachment_stream(futures_util::stream::iter(chunks), "image/png", "broken")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RequestError::Network(_)), "{err:?}");
+    assert_eq!(server.posts(MockServer::BLOG_NAME).len(), 1);
+}
+
+#[tokio::test]
+async fn user_info_and_limits() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    let info = client.user_info().send().await.unwrap();
+    assert_eq!(info.user.blogs[0].name, MockServer::BLOG_NAME);
+    server.set_remaining("posts", 3);
+    client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .send()
+        .await
+        .unwrap();
+    let limits = client.api_limits().send().await.unwrap();
+    assert_eq!(limits.user.posts.remaining, 2);
+    // the token should only have been requested once
+    assert_eq!(server.tokens_issued(), 1);
+}
+
+#[tokio::test]
+async fn await_builders_directly() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    let response = client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .tags("awaited")
+        .await
+        .unwrap();
+    let post = client
+        .post(MockServer::BLOG_NAME, response.id.parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(post.tags, ["awaited"]);
+    let user_info = client.user_info().await.unwrap();
+    assert_eq!(user_info.user.blogs[0].name, MockServer::BLOG_NAME);
+    let info = client.blog_info(MockServer::BLOG_NAME).await.unwrap();
+    assert_eq!(info.blog.posts, 1);
+    let limits = client.api_limits().await.unwrap();
+    assert_eq!(limits.user.posts.remaining, 249);
+    let raw = client
+        .raw(tumblr_api::backend::http::Method::GET, "user/info")
+        .await
+        .unwrap();
+    assert_eq!(raw["user"]["name"], user_info.user.name);
+
+    // errors come out of the await the same as out of `send`
+    assert!(client
+        .blog_info("not-my-blog")
+        .await
+        .unwrap_err()
+        .is_not_found());
+
+    // and the futures can be spawned, like `send`'s
+    let spawned = tokio::spawn(std::future::IntoFuture::into_future(client.user_info()));
+    assert_eq!(
+        spawned.await.unwrap().unwrap().user.name,
+        user_info.user.name
+    );
+}
+
+#[tokio::test]
+async fn unknown_blog() {
+    let server = MockServer::start().await.unwrap();
+    let err = server
+        .client()
+        .create_post("not-my-blog", vec![])
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+    assert!(!err.is_auth());
+    assert!(
+        matches!(err, RequestError::Api(err) if err.meta.status == 404 && err.has_error_code(ApiErrorCode::BlogNotFound))
+    );
+}
+
+#[tokio::test]
+async fn post_limit() {
+    let server = MockServer::start().await.unwrap();
+    server.set_remaining("posts", 0);
+    // no governor, so it's the api rejecting the post
+    let err = server
+        .client()
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_post_limit_exceeded());
+    assert!(
+        matches!(&err, RequestError::Api(err) if err.has_error_code(ApiErrorCode::PostLimitExceeded))
+    );
+}
+
+#[tokio::test]
+async fn faults() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    server.inject_fault(Fault::RateLimited);
+    server.inject_fault(Fault::ApiError {
+        status: 400,
+        code: 8001,
+        title: "Bad Request".into(),
+        detail: "invalid npf".into(),
+    });
+    let err = client.user_info().send().await.unwrap_err();
+    assert!(err.is_rate_limited());
+    assert!(matches!(err, RequestError::Api(err) if err.meta.status == 429));
+    let err = client.user_info().send().await.unwrap_err();
+    assert!(err.is_invalid_npf());
+    assert!(err.to_string().ends_with("(8001) Bad Request: invalid npf"));
+    assert!(
+        matches!(err, RequestError::Api(err) if err.errors[0].error_code() == ApiErrorCode::InvalidNpf)
+    );
+    // faults are used up once they've been returned
+    client.user_info().send().await.unwrap();
+}
+
+#[tokio::test]
+async fn unparsable_response() {
+    let server = MockServer::start().await.unwrap();
+    let body = r#"{"meta":{"status":201,"msg":"Created"},"response":{"id":false}}"#;
+    for keep_unparsable_bodies in [false, true] {
+        let client = tumblr_api::client::Client::builder(MockServer::credentials())
+            .api_base_url(server.api_base_url())
+            .oauth_token_url(server.oauth_token_url())
+            .keep_unparsable_bodies(keep_unparsable_bodies)
+            .build();
+        server.inject_fault(Fault::Raw {
+            status: 201,
+            body: body.into(),
+        });
+        let err = client
+            .create_post(MockServer::BLOG_NAME, vec![])
+            .send()
+            .await
+            .unwrap_err();
+        let RequestError::Deserializing { source, body: kept } = err else {
+            panic!("expected a deserializing error, got {err:?}");
+        };
+        assert_eq!(source.path, "response.id");
+        assert_eq!(kept.as_deref(), keep_unparsable_bodies.then_some(body));
+    }
+}
+
+#[tokio::test]
+async fn custom_http_client() {
+    let server = MockServer::start().await.unwrap();
+    let http_client = reqwest::Client::builder()
+        .user_agent("tumblr_api-tests")
+        .build()
+        .unwrap();
+    let client = tumblr_api::client::Client::builder(MockServer::credentials())
+        .http_client(http_client)
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .build();
+    client.user_info().send().await.unwrap();
+    assert_eq!(server.tokens_issued(), 1);
+}
+
+#[tokio::test]
+async fn custom_backend() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tumblr_api::backend::{bytes, http, BoxFuture, HttpBackend, TransportError};
+
+    /// passes requests through to reqwest, counting them on the way
+    struct Counting(reqwest::Client, Arc<AtomicUsize>);
+
+    impl HttpBackend for Counting {
+        fn send(
+            &self,
+            request: http::Request<bytes::Bytes>,
+        ) -> BoxFuture<'_, Result<http::Response<bytes::Bytes>, TransportError>> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            self.0.send(request)
+        }
+    }
+
+    let server = MockServer::start().await.unwrap();
+    let count = Arc::new(AtomicUsize::new(0));
+    let client = tumblr_api::client::Client::builder_with_backend(
+        MockServer::credentials(),
+        Counting(reqwest::Client::new(), count.clone()),
+    )
+    .api_base_url(server.api_base_url())
+    .oauth_token_url(server.oauth_token_url())
+    .build();
+    client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .add_attachment(vec![1, 2, 3], "image/png", "my-image")
+        .send()
+        .await
+        .unwrap();
+    // the token request goes through the backend too
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(server.posts(MockServer::BLOG_NAME)[0].attachments.len(), 1);
+}
+
+#[tokio::test]
+async fn bad_credentials() {
+    let server = MockServer::start().await.unwrap();
+    let client =
+        tumblr_api::client::Client::builder(tumblr_api::auth::Credentials::new("wrong", "wrong"))
+            .api_base_url(server.api_base_url())
+            .oauth_token_url(server.oauth_token_url())
+            .build();
+    let err = client.user_info().send().await.unwrap_err();
+    assert!(err.is_auth());
+    assert!(matches!(err, RequestError::Auth(_)));
+}
+
+#[tokio::test]
+async fn revoked_token() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    client.user_info().send().await.unwrap();
+    server.revoke_tokens();
+    // the rejected request is replayed with a new token
+    client.user_info().send().await.unwrap();
+    assert_eq!(server.tokens_issued(), 2);
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn cassette_record_and_replay() {
+    use tumblr_api::client::{cassette::Cassette, Client};
+    let path = std::env::temp_dir().join(format!(
+        "tumblr_api_cassette_test_{}.json",
+        std::process::id()
+    ));
+    let server = MockServer::start().await.unwrap();
+    let recording = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .record_cassette(&path)
+        .build();
+    let recorded = recording.user_info().send().await.unwrap();
+    recording.save_cassette().unwrap();
+    assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 1);
+    recording
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .send()
+        .await
+        .unwrap();
+    // the rest is written when the client is dropped
+    drop(recording);
+    drop(server);
+
+    let cassette = Cassette::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(cassette.interactions.len(), 2);
+    let serialized = serde_json::to_string(&cassette).unwrap();
+    assert!(!serialized.contains("mock-token"));
+
+    let replaying = Client::builder(tumblr_api::auth::Credentials::new("unused", "unused"))
+        .api_base_url(recorded_base_url(&cassette))
+        .replay_cassette(cassette)
+        .build();
+    let replayed = replaying.user_info().send().await.unwrap();
+    assert_eq!(replayed.user.name, recorded.user.name);
+    // each interaction is only replayed once
+    assert!(matches!(
+        replaying.user_info().send().await.unwrap_err(),
+        RequestError::Cassette(_)
+    ));
+}
+
+fn recorded_base_url(cassette: &tumblr_api::client::cassette::Cassette) -> String {
+    let url = &cassette.interactions[0].request.url;
+    url.strip_suffix("/user/info").unwrap().to_string()
+}
+
+#[tokio::test]
+async fn retries() {
+    use std::time::Duration;
+    use tumblr_api::client::{Client, RetryPolicy};
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .retry_policy(
+            RetryPolicy::new()
+                .max_attempts(3)
+                .initial_backoff(Duration::from_millis(1)),
+        )
+        .build();
+
+    server.inject_fault(Fault::ServerError(502));
+    server.inject_fault(Fault::ServerError(503));
+    client.user_info().send().await.unwrap();
+    assert_eq!(server.request_count(), 3);
+
+    // gives up after max_attempts
+    for _ in 0..3 {
+        server.inject_fault(Fault::ServerError(500));
+    }
+    let err = client.user_info().send().await.unwrap_err();
+    assert!(err.is_server_error());
+    assert!(matches!(
+        err,
+        RequestError::UnexpectedResponse { status, content_type: Some(content_type), body_snippet }
+            if status == 500 && content_type == "text/html" && body_snippet.contains("<h1>")
+    ));
+    assert_eq!(server.request_count(), 6);
+
+    // creating a post isn't idempotent, so isn't retried by default
+    server.inject_fault(Fault::ServerError(502));
+    assert!(client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .send()
+        .await
+        .is_err());
+    assert_eq!(server.request_count(), 7);
+}
+
+#[tokio::test]
+async fn rate_limit_headers() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    assert_eq!(client.rate_limit(), None);
+    let response = client.user_info().send_with_meta().await.unwrap();
+    let per_hour = response.rate_limit.unwrap().per_hour.unwrap();
+    assert_eq!((per_hour.limit, per_hour.remaining), (1000, 999));
+    client.api_limits().send().await.unwrap();
+    let per_day = client.rate_limit().unwrap().per_day.unwrap();
+    assert_eq!((per_day.limit, per_day.remaining), (5000, 4998));
+}
+
+#[tokio::test]
+async fn quota_governor() {
+    use tumblr_api::client::{Client, Quota, QuotaGovernor};
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .quota_governor(QuotaGovernor::new())
+        .build();
+    server.set_remaining("posts", 2);
+    for _ in 0..2 {
+        client
+            .create_post(MockServer::BLOG_NAME, vec![])
+            .send()
+            .await
+            .unwrap();
+    }
+    // one request to fetch the limits, plus the two posts
+    assert_eq!(server.request_count(), 3);
+    let err = client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_post_limit_exceeded());
+    assert!(matches!(
+        err,
+        RequestError::QuotaExhausted {
+            quota: Quota::Posts,
+            ..
+        }
+    ));
+    // the request should have been stopped before being sent
+    assert_eq!(server.request_count(), 3);
+
+    // concurrent requests can't overshoot the limit between them, and share one fetch of it
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .quota_governor(QuotaGovernor::new())
+        .build();
+    server.set_remaining("posts", 2);
+    let results = futures_util::future::join_all(
+        (0..5).map(|_| client.create_post(MockServer::BLOG_NAME, vec![]).send()),
+    )
+    .await;
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 2);
+    assert_eq!(server.posts(MockServer::BLOG_NAME).len(), 2);
+    assert_eq!(server.request_count(), 3);
+
+    // quota set aside for a request that fails is given back
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .quota_governor(QuotaGovernor::new())
+        .build();
+    server.set_remaining("posts", 2);
+    let create_post = || client.create_post(MockServer::BLOG_NAME, vec![]).send();
+    create_post().await.unwrap();
+    server.inject_fault(Fault::ServerError(500));
+    create_post().await.unwrap_err();
+    create_post().await.unwrap();
+    assert_eq!(server.posts(MockServer::BLOG_NAME).len(), 2);
+}
+
+#[tokio::test]
+async fn middleware() {
+    use std::sync::{Arc, Mutex};
+    use tumblr_api::{
+        backend::http,
+        client::{Client, Middleware, ResponseInfo},
+    };
+
+    /// adds a header to every request
+    struct AddHeader;
+
+    impl Middleware for AddHeader {
+        fn on_request(
+            &self,
+            _endpoint: &'static str,
+            request: &mut http::Request<bytes::Bytes>,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            request
+                .headers_mut()
+                .insert("x-test", http::HeaderValue::from_static("hi"));
+            Ok(())
+        }
+    }
+
+    /// records what it sees, and blocks requests to `user/limits`
+    #[derive(Default)]
+    struct Log(Mutex<Vec<String>>);
+
+    impl Middleware for Log {
+        fn on_request(
+            &self,
+            endpoint: &'static str,
+            request: &mut http::Request<bytes::Bytes>,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            let header = request.headers()["x-test"].to_str()?;
+            self.0.lock().unwrap().push(format!("{endpoint} {header}"));
+            if endpoint == "user/limits" {
+                return Err("no limits allowed".into());
+            }
+            Ok(())
+        }
+
+        fn on_response(&self, response: &ResponseInfo<'_>) {
+            self.0.lock().unwrap().push(format!(
+                "{} {} {}",
+                response.endpoint,
+                response.status.as_u16(),
+                response.meta.map_or("-", |meta| &meta.msg),
+            ));
+        }
+    }
+
+    let server = MockServer::start().await.unwrap();
+    let log = Arc::new(Log::default());
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .middleware(AddHeader)
+        .middleware(log.clone())
+        .build();
+    client.user_info().send().await.unwrap();
+    client
+        .create_post("not-my-blog", vec![])
+        .send()
+        .await
+        .unwrap_err();
+    let err = client.api_limits().send().await.unwrap_err();
+    assert!(matches!(err, RequestError::Middleware(_)));
+    assert_eq!(server.request_count(), 2);
+    assert_eq!(
+        *log.0.lock().unwrap(),
+        [
+            "user/info hi",
+            "user/info 200 OK",
+            "blog/{blog_identifier}/posts hi",
+            "blog/{blog_identifier}/posts 404 Not Found",
+            "user/limits hi",
+        ]
+    );
+
+    // a request that's sent again with a new token still only goes through each hook once
+    log.0.lock().unwrap().clear();
+    server.revoke_tokens();
+    client.user_info().send().await.unwrap();
+    assert_eq!(server.request_count(), 4);
+    assert_eq!(*log.0.lock().unwrap(), ["user/info hi", "user/info 200 OK"]);
+}
+
+#[tokio::test]
+async fn pagination() {
+    use futures_util::{StreamExt, TryStreamExt};
+    use tumblr_api::api::BlogPostsEndpoint;
+
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    for i in 0..5 {
+        client
+            .create_post(MockServer::BLOG_NAME, vec![])
+            .tags(format!("post {i}"))
+            .send()
+            .await
+            .unwrap();
+    }
+    for _ in 0..3 {
+        client
+            .create_post(MockServer::BLOG_NAME, vec![])
+            .initial_state(CreatePostState::Draft)
+            .send()
+            .await
+            .unwrap();
+    }
+    let endpoint = BlogPostsEndpoint {
+        blog_identifier: MockServer::BLOG_NAME.into(),
+        page_size: Some(2),
+        ..Default::default()
+    };
+    let tags = |posts: Vec<tumblr_api::api::NPFPost>| -> Vec<String> {
+        posts.into_iter().map(|post| post.tags.join(",")).collect()
+    };
+
+    // everything, newest first
+    let requests_before = server.request_count();
+    let posts: Vec<_> = client
+        .paginate(endpoint.clone())
+        .items()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        tags(posts),
+        ["post 4", "post 3", "post 2", "post 1", "post 0"]
+    );
+    assert_eq!(server.request_count() - requests_before, 3);
+
+    // pages aren't fetched once the limit's reached
+    let requests_before = server.request_count();
+    let posts: Vec<_> = client
+        .paginate(endpoint.clone())
+        .limit(3)
+        .items()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(tags(posts), ["post 4", "post 3", "post 2"]);
+    assert_eq!(server.request_count() - requests_before, 2);
+
+    // a page cut short by the limit resumes from the first item that wasn't yielded
+    let pages: Vec<_> = client
+        .paginate(endpoint.clone())
+        .limit(3)
+        .pages()
+        .try_collect()
+        .await
+        .unwrap();
+    let rest: Vec<_> = client
+        .paginate(endpoint.clone())
+        .resume_from(pages.last().unwrap().next.clone().unwrap())
+        .items()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(tags(rest), ["post 1", "post 0"]);
+    let drafts: Vec<_> = client
+        .drafts(MockServer::BLOG_NAME)
+        .limit(1)
+        .pages()
+        .try_collect()
+        .await
+        .unwrap();
+    let remaining_drafts: Vec<_> = client
+        .drafts(MockServer::BLOG_NAME)
+        .resume_from(drafts[0].next.clone().unwrap())
+        .items()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(remaining_drafts.len(), 2);
+
+    // resuming from a saved cursor
+    let mut pages = std::pin::pin!(client.paginate(endpoint.clone()).pages());
+    let first = pages.next().await.unwrap().unwrap();
+    let cursor = first.next.unwrap();
+    let cursor: tumblr_api::api::Cursor =
+        serde_json::from_str(&serde_json::to_string(&cursor).unwrap()).unwrap();
+    let rest: Vec<_> = client
+        .paginate(endpoint)
+        .resume_from(cursor)
+        .items()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(tags(rest), ["post 2", "post 1", "post 0"]);
+
+    // drafts are paginated by id rather than offset
+    let drafts: Vec<_> = client
+        .drafts(MockServer::BLOG_NAME)
+        .items()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(drafts.len(), 3);
+    assert!(drafts.windows(2).all(|pair| pair[0].id > pair[1].id));
+}
+
+#[tokio::test]
+async fn response_cache() {
+    use std::time::Duration;
+    use tumblr_api::client::{cache::ResponseCache, Client};
+
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .cache(ResponseCache::in_memory(16).ttl(Duration::from_millis(200)))
+        .build();
+    let id: i64 = client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .tags("cached")
+        .send()
+        .await
+        .unwrap()
+        .id
+        .parse()
+        .unwrap();
+
+    let requests_before = server.request_count();
+    for _ in 0..3 {
+        let info = client
+            .blog_info(MockServer::BLOG_NAME)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(info.blog.posts, 1);
+        let post = client.post(MockServer::BLOG_NAME, id).send().await.unwrap();
+        assert_eq!(post.tags, ["cached"]);
+    }
+    assert_eq!(server.request_count() - requests_before, 2);
+
+    // once the ttl is up, responses are revalidated (and reused, since they haven't changed)
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let requests_before = server.request_count();
+    let post = client.post(MockServer::BLOG_NAME, id).send().await.unwrap();
+    assert_eq!(post.tags, ["cached"]);
+    client.post(MockServer::BLOG_NAME, id).send().await.unwrap();
+    assert_eq!(server.request_count() - requests_before, 1);
+
+    // errors aren't cached
+    for _ in 0..2 {
+        assert!(client
+            .blog_info("not-my-blog")
+            .send()
+            .await
+            .unwrap_err()
+            .is_not_found());
+    }
+    assert_eq!(server.request_count() - requests_before, 3);
+}
+
+#[tokio::test]
+async fn cached_responses_dont_need_a_token() {
+    use std::time::Duration;
+    use tumblr_api::client::{cache::ResponseCache, Client};
+
+    let server = MockServer::start().await.unwrap();
+    // tokens this short-lived are already treated as expired, so every request that reaches the
+    // api needs a new one
+    server.set_token_lifetime(Duration::from_secs(10));
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .cache(ResponseCache::in_memory(16))
+        .build();
+    for _ in 0..3 {
+        client
+            .blog_info(MockServer::BLOG_NAME)
+            .send()
+            .await
+            .unwrap();
+    }
+    assert_eq!(server.tokens_issued(), 1);
+    client.user_info().send().await.unwrap();
+    assert_eq!(server.tokens_issued(), 2);
+}
+
+#[tokio::test]
+async fn avatar() {
+    use tumblr_api::client::{cache::ResponseCache, Client};
+
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .cache(ResponseCache::in_memory(16))
+        .build();
+
+    let requests_before = server.request_count();
+    for _ in 0..2 {
+        let avatar = client
+            .avatar(MockServer::BLOG_NAME)
+            .size(128)
+            .send()
+            .await
+            .unwrap();
+        assert!(avatar.avatar_url.ends_with("_128.png"));
+    }
+    // the redirect isn't followed, and is cached like any other response
+    assert_eq!(server.request_count() - requests_before, 1);
+    let avatar = client.avatar(MockServer::BLOG_NAME).send().await.unwrap();
+    assert!(avatar.avatar_url.ends_with("_64.png"));
+
+    assert!(client
+        .avatar("not-my-blog")
+        .send()
+        .await
+        .unwrap_err()
+        .is_not_found());
+}
+
+#[tokio::test]
+async fn batch() {
+    use tumblr_api::client::batch::{Operation, OperationOutput};
+
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    let mut ids = Vec::new();
+    for i in 0..2 {
+        let response = client
+            .create_post(
+                MockServer::BLOG_NAME,
+                vec![npf::ContentBlockText::builder(format!("post {i}")).build()],
+            )
+            .send()
+            .await
+            .unwrap();
+        ids.push(response.id.parse::<i64>().unwrap());
+    }
+
+    let report = client
+        .batch()
+        .concurrency(2)
+        .push(Operation::Like {
+            post_id: ids[0],
+            reblog_key: format!("mock-reblog-key-{}", ids[0]),
+        })
+        .push(Operation::Reblog {
+            blog_identifier: MockServer::BLOG_NAME.into(),
+            parent_blog_uuid: "t:mock".into(),
+            parent_post_id: ids[0],
+            reblog_key: format!("mock-reblog-key-{}", ids[0]),
+            tags: Some("reblogged".into()),
+        })
+        .operations([
+            Operation::EditTags {
+                blog_identifier: MockServer::BLOG_NAME.into(),
+                post_id: ids[1],
+                tags: "x,y".into(),
+            },
+            Operation::Delete {
+                blog_identifier: MockServer::BLOG_NAME.into(),
+                post_id: 1,
+            },
+            Operation::Delete {
+                blog_identifier: MockServer::BLOG_NAME.into(),
+                post_id: ids[0],
+            },
+        ])
+        .run()
+        .await;
+
+    assert_eq!(report.entries.len(), 5);
+    assert!(!report.all_succeeded());
+    assert!(matches!(
+        report.entries[0].result,
+        Ok(OperationOutput::Liked)
+    ));
+    let Ok(OperationOutput::Reblogged { post_id }) = &report.entries[1].result else {
+        panic!("reblog failed: {:?}", report.entries[1].result);
+    };
+    assert!(matches!(
+        report.entries[2].result,
+        Ok(OperationOutput::TagsEdited)
+    ));
+    assert!(matches!(
+        report.entries[4].result,
+        Ok(OperationOutput::Deleted)
+    ));
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, 3);
+    assert!(failures[0].2.is_not_found());
+
+    assert_eq!(server.liked_posts(), [ids[0]]);
+    let posts = server.posts(MockServer::BLOG_NAME);
+    let remaining: Vec<_> = posts.iter().map(|post| post.id.to_string()).collect();
+    assert_eq!(remaining, [ids[1].to_string(), post_id.clone()]);
+    assert_eq!(posts[0].tags, ["x", "y"]);
+    // editing the tags leaves the content alone
+    assert_eq!(
+        posts[0].content,
+        serde_json::json!([{"type": "text", "text": "post 1"}])
+    );
+    assert_eq!(posts[1].tags, ["reblogged"]);
+}
+
+#[tokio::test]
+async fn batch_invalidates_cache() {
+    use tumblr_api::client::{batch::Operation, cache::ResponseCache, Client};
+
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .cache(ResponseCache::in_memory(16))
+        .build();
+    let id: i64 = client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .tags("before")
+        .send()
+        .await
+        .unwrap()
+        .id
+        .parse()
+        .unwrap();
+    let post = client.post(MockServer::BLOG_NAME, id).send().await.unwrap();
+    assert_eq!(post.tags, ["before"]);
+    let info = client
+        .blog_info(MockServer::BLOG_NAME)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(info.blog.posts, 1);
+
+    let edit = Operation::EditTags {
+        blog_identifier: MockServer::BLOG_NAME.into(),
+        post_id: id,
+        tags: "after".into(),
+    };
+    assert!(client.batch().push(edit).run().await.all_succeeded());
+    let post = client.post(MockServer::BLOG_NAME, id).send().await.unwrap();
+    assert_eq!(post.tags, ["after"]);
+
+    let delete = Operation::Delete {
+        blog_identifier: MockServer::BLOG_NAME.into(),
+        post_id: id,
+    };
+    assert!(client.batch().push(delete).run().await.all_succeeded());
+    assert!(client
+        .post(MockServer::BLOG_NAME, id)
+        .send()
+        .await
+        .unwrap_err()
+        .is_not_found());
+    let info = client
+        .blog_info(MockServer::BLOG_NAME)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(info.blog.posts, 0);
+}
+
+#[tokio::test]
+async fn dry_run() {
+    use tumblr_api::api::{PreparedBody, PreparedPart};
+
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    let prepared = client
+        .create_post(
+            MockServer::BLOG_NAME,
+            vec![npf::ContentBlockText::builder("hello").build()],
+        )
+        .tags("a,b")
+        .add_attachment(vec![0; 16], "image/png", "img")
+        .add_attachment_stream(
+            futures_util::stream::empty::<Result<bytes::Bytes, std::io::Error>>(),
+            "video/mp4",
+            "vid",
+        )
+        .dry_run()
+        .unwrap();
+    assert_eq!(prepared.method, "POST");
+    assert_eq!(
+        prepared.url,
+        format!(
+            "{}/blog/{}/posts",
+            server.api_base_url(),
+            MockServer::BLOG_NAME
+        )
+    );
+    let Some(PreparedBody::Multipart { json, parts }) = prepared.body else {
+        panic!("expected a multipart body, got {:?}", prepared.body);
+    };
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "content": [{ "type": "text", "text": "hello" }],
+            "tags": "a,b",
+        })
+    );
+    assert_eq!(
+        parts,
+        [
+            PreparedPart {
+                name: "img".into(),
+                mime_type: "image/png".into(),
+                len: Some(16),
+            },
+            PreparedPart {
+                name: "vid".into(),
+                mime_type: "video/mp4".into(),
+                len: None,
+            }
+        ]
+    );
+
+    let prepared = client.blog_info(MockServer::BLOG_NAME).dry_run().unwrap();
+    assert_eq!(prepared.method, "GET");
+    assert_eq!(prepared.body, None);
+
+    // invalid requests fail the same way they would when sent
+    assert!(client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .add_attachment(vec![], "not a mime type", "img")
+        .dry_run()
+        .is_err());
+    assert!(client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .add_attachment_stream(
+            futures_util::stream::empty::<Result<bytes::Bytes, std::io::Error>>(),
+            "not a mime type",
+            "vid",
+        )
+        .dry_run()
+        .is_err());
+
+    // and nothing was actually sent
+    assert_eq!(server.request_count(), 0);
+    assert_eq!(server.tokens_issued(), 0);
+}
+
+#[tokio::test]
+async fn raw_requests() {
+    use tumblr_api::backend::http::Method;
+
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+
+    let response = client
+        .raw(
+            Method::POST,
+            format!("blog/{}/posts", MockServer::BLOG_NAME),
+        )
+        .json(serde_json::json!({ "content": [], "tags": "raw" }))
+        .add_attachment(vec![1, 2, 3], "image/png", "img")
+        .send_with_meta::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(response.status, 201);
+    let posts = server.posts(MockServer::BLOG_NAME);
+    assert_eq!(posts[0].tags, ["raw"]);
+    assert_eq!(posts[0].attachments, ["img"]);
+
+    let page = client
+        .raw(
+            Method::GET,
+            format!("/blog/{}/posts", MockServer::BLOG_NAME),
+        )
+        .query("limit", "1")
+        .await
+        .unwrap();
+    assert_eq!(page["total_posts"], 1);
+    assert_eq!(page["posts"][0]["tags"][0], "raw");
+
+    // links the api gives out can be followed as they are
+    client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .tags("newer")
+        .send()
+        .await
+        .unwrap();
+    let page = client
+        .raw(Method::GET, format!("blog/{}/posts", MockServer::BLOG_NAME))
+        .query("limit", "1")
+        .await
+        .unwrap();
+    assert_eq!(page["posts"][0]["tags"][0], "newer");
+    let next = page["_links"]["next"]["href"].as_str().unwrap();
+    assert!(next.starts_with("/v2/") && next.contains('?'));
+    let page = client
+        .raw(Method::GET, next)
+        .query("limit", "1")
+        .await
+        .unwrap();
+    assert_eq!(page["posts"][0]["tags"][0], "raw");
+
+    // typed responses
+    let info: tumblr_api::api::UserInfoResponse = client
+        .raw(Method::GET, "user/info")
+        .send_as()
+        .await
+        .unwrap();
+    assert_eq!(info.user.blogs[0].name, MockServer::BLOG_NAME);
+
+    // errors are parsed like they are for any other endpoint
+    let err = client
+        .raw(Method::GET, "not/an/endpoint")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_client() {
+    // the mock server needs a runtime of its own to run on, since the blocking client will be
+    // blocking this thread
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(MockServer::start()).unwrap();
+    let client = tumblr_api::client::blocking::Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .build()
+        .unwrap();
+    let user_info = client.user_info().send().unwrap();
+    assert_eq!(user_info.user.blogs[0].name, MockServer::BLOG_NAME);
+    let response = client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .tags("a,b")
+        .send_with_meta()
+        .unwrap();
+    assert_eq!(response.status, 201);
+    assert_eq!(server.posts(MockServer::BLOG_NAME)[0].tags, ["a", "b"]);
+
+    for _ in 0..2 {
+        client
+            .create_post(MockServer::BLOG_NAME, vec![])
+            .send()
+            .unwrap();
+    }
+    let posts: Vec<_> = client
+        .paginate(tumblr_api::api::BlogPostsEndpoint {
+            blog_identifier: MockServer::BLOG_NAME.into(),
+            page_size: Some(2),
+            ..Default::default()
+        })
+        .items()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(posts.len(), 3);
+    assert_eq!(client.drafts(MockServer::BLOG_NAME).pages().count(), 1);
+}
+
+#[cfg(feature = "profiles")]
+#[tokio::test]
+async fn profiles() {
+    use tumblr_api::client::profiles::{Profile, Profiles};
+
+    let server = MockServer::start().await.unwrap();
+    server.add_blog("other-blog");
+    let profile = |blogs: &[&str]| Profile {
+        consumer_key: MockServer::CONSUMER_KEY.into(),
+        consumer_secret: MockServer::CONSUMER_SECRET.into(),
+        blogs: blogs.iter().map(|blog| (*blog).to_string()).collect(),
+        api_base_url: Some(server.api_base_url()),
+        oauth_token_url: Some(server.oauth_token_url()),
+    };
+    let mut profiles = Profiles::default();
+    profiles.insert("a", profile(&[MockServer::BLOG_NAME]));
+    profiles.insert("b", profile(&["other-blog"]));
+    let clients = profiles.clients();
+    assert_eq!(
+        clients.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+        ["a", "b"]
+    );
+
+    for blog in [MockServer::BLOG_NAME, "other-blog"] {
+        clients
+            .for_blog(blog)
+            .unwrap()
+            .create_post(blog, vec![])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(server.posts(blog).len(), 1);
+    }
+    assert!(clients.for_blog("nobody").is_none());
+    // each profile gets its own token
+    clients.get("a").unwrap().user_info().send().await.unwrap();
+    assert_eq!(server.tokens_issued(), 2);
+}
+
+#[tokio::test]
+async fn timeouts() {
+    use futures_util::TryStreamExt;
+    use std::time::Duration;
+    use tumblr_api::{
+        backend::TransportErrorKind,
+        client::{batch::Operation, Client},
+    };
+
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder(MockServer::credentials())
+        .api_base_url(server.api_base_url())
+        .oauth_token_url(server.oauth_token_url())
+        .timeout(Duration::from_millis(100))
+        .build();
+    server.inject_fault(Fault::Delay(Duration::from_millis(500)));
+    let err = client.user_info().send().await.unwrap_err();
+    assert!(
+        matches!(&err, RequestError::Network(err) if err.kind() == TransportErrorKind::Timeout),
+        "{err:?}"
+    );
+
+    // individual requests (e.g. big uploads) can be given longer
+    server.inject_fault(Fault::Delay(Duration::from_millis(300)));
+    client
+        .create_post(MockServer::BLOG_NAME, vec![])
+        .add_attachment(vec![0; 1024], "image/png", "big-image")
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(server.posts(MockServer::BLOG_NAME).len(), 1);
+    assert_eq!(server.tokens_issued(), 1);
+
+    // ...and so can each request paginators and batches make
+    server.inject_fault(Fault::Delay(Duration::from_millis(300)));
+    let posts: Vec<_> = client
+        .blog_posts(MockServer::BLOG_NAME)
+        .timeout(Duration::from_secs(5))
+        .items()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(posts.len(), 1);
+    // (retagging fetches the post before editing it)
+    server.inject_fault(Fault::Delay(Duration::from_millis(300)));
+    server.inject_fault(Fault::Delay(Duration::from_millis(300)));
+    let report = client
+        .batch()
+        .timeout(Duration::from_secs(5))
+        .push(Operation::EditTags {
+            blog_identifier: MockServer::BLOG_NAME.into(),
+            post_id: posts[0].id,
+            tags: "slow".into(),
+        })
+        .run()
+        .await;
+    assert!(report.all_succeeded(), "{:?}", report.entries);
+}
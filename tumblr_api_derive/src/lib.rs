@@ -27,6 +27,7 @@ struct BuilderInputReceiver {
     build_fn: Option<BuildFnInfo>,
     #[darling(default, rename = "ctor")]
     ctor_options: CtorOptions,
+    into_future: Option<IntoFutureOptions>,
 }
 
 #[derive(Debug, FromMeta, Default)]
@@ -35,6 +36,16 @@ struct CtorOptions {
     visibility: Option<Visibility>,
 }
 
+/// implement `IntoFuture` for the builder by calling its (async) send method, so the builder can
+/// be `.await`ed directly
+#[derive(Debug, FromMeta)]
+struct IntoFutureOptions {
+    /// what the send method returns
+    output: syn::Type,
+    /// the name of the send method, `send` by default
+    send_fn: Option<Ident>,
+}
+
 #[derive(Debug, FromField)]
 #[darling(attributes(builder))]
 struct BuilderFieldReceiver {
@@ -46,6 +57,7 @@ struct BuilderFieldReceiver {
 }
 
 #[derive(Debug, FromMeta)]
+#[allow(clippy::large_enum_variant)]
 enum BuilderFieldSetMode {
     Ctor(darling::util::Override<BuilderFieldSetViaCtor>),
     Setter(BuilderFieldSetViaSetter),
@@ -263,6 +275,26 @@ impl BuilderInputReceiver {
             }
         });
 
+        if let Some(into_future) = &self.into_future {
+            let output = &into_future.output;
+            let send_fn = match &into_future.send_fn {
+                Some(ident) => ident.clone(),
+                None => format_ident!("send"),
+            };
+            ret.extend(quote! {
+                impl ::core::future::IntoFuture for #builder_setters_impl_target {
+                    type Output = #output;
+                    type IntoFuture = ::core::pin::Pin<::std::boxed::Box<
+                        dyn ::core::future::Future<Output = Self::Output> + ::core::marker::Send
+                    >>;
+
+                    fn into_future(self) -> Self::IntoFuture {
+                        ::std::boxed::Box::pin(self.#send_fn())
+                    }
+                }
+            });
+        }
+
         Ok(ret)
     }
 }
@@ -275,7 +307,7 @@ fn strip_option_from(ty: &syn::Type) -> Result<syn::Type, darling::Error> {
 
     if a.len() >= 4 {
         use proc_macro2::TokenTree;
-        let first = a.get(0).unwrap();
+        let first = a.first().unwrap();
         let next = a.get(1).unwrap();
         let last = a.last().unwrap();
         if let (TokenTree::Ident(first), TokenTree::Punct(next), TokenTree::Punct(last)) =
@@ -1,5 +1,8 @@
 
-use std::sync::{Arc, Mutex};
+use std::{
+    future::IntoFuture,
+    sync::{Arc, Mutex},
+};
 
 use tumblr_api_derive::Builder;
 
@@ -35,3 +38,35 @@ fn foo() {
 
     let _: Bar = Bar::builder("a", 1).build();
 }
+
+#[test]
+fn into_future() {
+    #[derive(Builder)]
+    #[builder(into_future(output = "u32"))]
+    struct Baz {
+        #[builder(set(ctor))]
+        a: u32,
+        #[builder(set(setter()))]
+        b: u32,
+    }
+
+    impl Baz {
+        async fn send(self) -> u32 {
+            self.a + self.b
+        }
+    }
+
+    #[derive(Builder)]
+    #[builder(into_future(output = "u32", send_fn = go))]
+    struct Qux {}
+
+    impl Qux {
+        async fn go(self) -> u32 {
+            0
+        }
+    }
+
+    fn assert_into_future<T: IntoFuture<Output = u32>>(_: T) {}
+    assert_into_future(Baz::new(1).b(2));
+    assert_into_future(Qux::new());
+}